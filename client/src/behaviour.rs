@@ -1,22 +1,53 @@
-use crate::cli::get_user_input;
+use crate::cli::{display_chat_message, get_user_input, parse_chat_command};
 pub use asking_player::handle_asking_role;
-use guessing_game::messages::{Message, Password, PlayerId, Riddle, Streamable};
+use guessing_game::client_core::{GameClient, GameEvent};
+use guessing_game::logging::{log_error, log_warn};
+use guessing_game::messages::{PlayerId, Streamable, MAX_CHAT_MESSAGE_LENGTH};
 pub use guessing_player::handle_guessing_role;
 
 mod asking_player;
 mod guessing_player;
 
-pub fn login(stream: &mut impl Streamable) -> Result<PlayerId, String> {
+pub fn login(client: &mut GameClient<impl Streamable>) -> Result<PlayerId, String> {
     let password = get_user_input("Please provide server password:");
-    let password = Password { password };
-    stream
-        .write(&Message::Password(password))
-        .map_err(|e| e.to_string())?;
-    let message = stream.read().map_err(|e| e.to_string())?;
-
-    if let Message::PlayerId(player) = message {
-        return Ok(player);
+
+    client.login(&password).map_err(|e| e.to_string())
+}
+
+pub fn next_game_event(client: &mut GameClient<impl Streamable>) -> Result<GameEvent, String> {
+    loop {
+        match client.next_event().map_err(|e| e.to_string())? {
+            GameEvent::Chat(chat_message) => display_chat_message(&chat_message),
+            GameEvent::Error(error_message) => log_error(error_message),
+            event => return Ok(event),
+        }
+    }
+}
+
+pub fn get_user_input_with_chat(
+    client: &mut GameClient<impl Streamable>,
+    message_for_user: &str,
+) -> Result<String, String> {
+    loop {
+        let input = get_user_input(message_for_user);
+        let Some((scope, text)) = parse_chat_command(&input) else {
+            return Ok(input);
+        };
+
+        if text.is_empty() || text.len() > MAX_CHAT_MESSAGE_LENGTH {
+            log_warn(format!(
+                "Chat message must have between 1 and {} characters",
+                MAX_CHAT_MESSAGE_LENGTH
+            ));
+            continue;
+        }
+
+        client.chat(scope, &text).map_err(|e| e.to_string())?;
     }
+}
+
+pub fn get_hint(client: &mut GameClient<impl Streamable>) -> Result<Option<String>, String> {
+    let hint = get_user_input_with_chat(client, "You can provide a hint for user:")?;
 
-    Err("Server Error. Invalid type returned".into())
+    Ok(Some(hint).filter(|hint| !hint.is_empty()))
 }
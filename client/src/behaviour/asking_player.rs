@@ -1,97 +1,110 @@
 use super::*;
-use crate::cli::{get_hint, get_opponent, get_question};
+use crate::cli::{get_category, get_difficulty, get_max_guesses, get_opponent, get_question};
 use guessing_game::logging::{log_info, log_warn};
-use guessing_game::messages::{Evaluation, Message, Role, Streamable};
+use guessing_game::messages::{PlayerId, Riddle, Role, Streamable};
 
-pub enum ResponseResult {
-    WrongAnswer,
-    Quit,
-    RightAnswer,
-}
-
-pub fn process_response_from_guessing_player(
-    stream: &mut impl Streamable,
-) -> Result<ResponseResult, String> {
-    let response = stream.read().map_err(|e| e.to_string())?;
-
-    if let Message::Evaluation(evaluation) = response {
-        if evaluation.guessed {
-            return Ok(ResponseResult::RightAnswer);
-        }
-
-        return Ok(ResponseResult::WrongAnswer);
+fn get_guessing_players_for_category(
+    client: &mut GameClient<impl Streamable>,
+    category: &str,
+    guessing_players: &[PlayerId],
+) -> Result<Vec<PlayerId>, String> {
+    if category.is_empty() {
+        return Ok(guessing_players.to_vec());
     }
 
-    if let Message::PlayerNotAvailable(_) = response {
-        log_warn("Opponent is not available anymore");
-        return Ok(ResponseResult::Quit);
+    let players_in_category = client
+        .list_opponents(Some(category.into()))
+        .map_err(|e| e.to_string())?;
+
+    if players_in_category.is_empty() {
+        log_warn(format!(
+            "No guessing players prefer category {}, showing all players",
+            category
+        ));
+        return Ok(guessing_players.to_vec());
     }
 
-    log_warn("Received message not within role");
+    Ok(players_in_category)
+}
 
-    Err("Received message not within role".into())
+fn send_evaluation(
+    client: &mut GameClient<impl Streamable>,
+    guessed: bool,
+    hint: Option<String>,
+) -> Result<(), String> {
+    client.evaluate(guessed, hint).map_err(|e| e.to_string())
 }
 
 pub fn handle_asking_role(
-    stream: &mut impl Streamable,
-    player_id: &str,
-    guessing_players: &[String],
+    client: &mut GameClient<impl Streamable>,
+    guessing_players: &[PlayerId],
 ) -> Result<(), String> {
-    stream
-        .write(&Message::RegisterPlayerRole(Role::AskingPlayer))
+    client
+        .register_role(Role::AskingPlayer.into())
         .map_err(|e| e.to_string())?;
-    let guessing_player_id = get_opponent(guessing_players);
+    let category = get_category();
+    let guessing_players = get_guessing_players_for_category(client, &category, guessing_players)?;
+    let guessing_player_id = get_opponent(&guessing_players);
     log_info("Asking player opponent selected.");
-    let question = get_question(&guessing_player_id);
-    let hint = get_hint();
+    let question = get_question(guessing_player_id);
+    let hint = get_hint(client)?;
+    let max_guesses = get_max_guesses();
+    let difficulty = get_difficulty();
     let riddle = Riddle {
-        sender: player_id.into(),
-        asking_player: player_id.into(),
-        guessing_player: guessing_player_id.clone(),
+        guessing_player: guessing_player_id,
         message: question,
         hint,
+        max_guesses,
+        category,
+        difficulty,
+        ..Default::default()
     };
 
-    let riddle = Message::Riddle(riddle);
-    stream.write(&riddle).map_err(|e| e.to_string())?;
+    client.send_riddle(riddle).map_err(|e| e.to_string())?;
     let mut guesses = 0;
 
     loop {
         log_info("Please wait for answer from guessing player.");
-        guesses += 1;
-        match process_response_from_guessing_player(stream)? {
-            ResponseResult::WrongAnswer => {
+        match next_game_event(client)? {
+            GameEvent::GuessEvaluated { guessed: false } => {
+                guesses += 1;
                 log_info(format!("User has not guessed. This is {}. try", guesses));
-                let hint = get_hint();
-                let evaluation = Evaluation {
-                    hint,
-                    guessed: false,
-                };
-                stream
-                    .write(&Message::Evaluation(evaluation))
-                    .map_err(|e| e.to_string())?;
+                let hint = get_hint(client)?;
+                send_evaluation(client, false, hint)?;
             }
-            ResponseResult::RightAnswer => {
+            GameEvent::GuessEvaluated { guessed: true } => {
+                guesses += 1;
                 log_info(format!(
                     "User has guessed. You lost.  This is {}. try",
                     guesses
                 ));
-                let evaluation = Evaluation {
-                    hint: None,
-                    guessed: true,
-                };
-                stream
-                    .write(&Message::Evaluation(evaluation))
-                    .map_err(|e| e.to_string())?;
+                send_evaluation(client, true, None)?;
                 return Ok(());
             }
-            ResponseResult::Quit => {
+            GameEvent::HintRequested => {
+                log_info("User has requested a hint.");
+                let hint = get_hint(client)?;
+                send_evaluation(client, false, hint)?;
+            }
+            GameEvent::GameOver(game_over) => {
+                log_info(format!(
+                    "User has run out of guesses. You won. The answer was: {}",
+                    game_over.answer
+                ));
+                return Ok(());
+            }
+            GameEvent::PlayerNotAvailable(_) => {
+                log_warn("Opponent is not available anymore");
                 log_info(format!(
                     "User has quit. You wonn.  They made {} tries",
                     guesses
                 ));
                 return Ok(());
             }
+            _ => {
+                log_warn("Received message not within role");
+                return Err("Received message not within role".into());
+            }
         }
     }
 }
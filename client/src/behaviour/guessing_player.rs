@@ -1,83 +1,101 @@
 use super::*;
-use crate::cli::{get_user_input, get_wants_to_quit};
+use crate::cli::get_wants_to_quit;
 use guessing_game::logging::{log_info, log_warn};
-use guessing_game::messages::{Message, Streamable};
+use guessing_game::messages::{Streamable, HOUSE_PLAYER_ID};
 
-fn send_guess(
-    stream: &mut impl Streamable,
-    guessing_player: &str,
-    asking_player: &str,
-) -> Result<(), String> {
-    let guess = get_user_input("Provide guess:");
+fn send_guess(client: &mut GameClient<impl Streamable>) -> Result<bool, String> {
+    let guess =
+        get_user_input_with_chat(client, "Provide guess, or write /hint to ask for a hint:")?;
 
-    let guess = Riddle {
-        sender: guessing_player.into(),
-        asking_player: asking_player.into(),
-        guessing_player: guessing_player.into(),
-        hint: None,
-        message: guess,
-    };
+    if guess == "/hint" {
+        client.request_hint().map_err(|e| e.to_string())?;
+        log_info("Waiting for hint");
+        return Ok(false);
+    }
 
-    stream
-        .write(&Message::Riddle(guess))
-        .map_err(|e| e.to_string())?;
+    client.guess(&guess).map_err(|e| e.to_string())?;
     log_info("Waiting for evaluation");
-    Ok(())
+    Ok(true)
 }
 
-pub fn handle_guessing_role(stream: &mut impl Streamable, player_id: &str) -> Result<(), String> {
+pub fn handle_guessing_role(client: &mut GameClient<impl Streamable>) -> Result<(), String> {
     let mut guess_count = 0;
-    let mut asking_player_id: Option<String> = None;
 
     loop {
-        match stream.read().map_err(|e| e.to_string())? {
-            Message::PlayerNotAvailable(id) => {
+        match next_game_event(client)? {
+            GameEvent::PlayerNotAvailable(id) if id == HOUSE_PLAYER_ID => {
+                log_warn("The house has no riddles available");
+                return Ok(());
+            }
+            GameEvent::PlayerNotAvailable(id) => {
                 log_warn(format!(
                     "Asking player with id {} has exited before game end",
                     id
                 ));
                 return Ok(());
             }
-            Message::Evaluation(evaluation) => {
-                if guess_count == 0 || asking_player_id.is_none() {
+            GameEvent::Evaluated(evaluation) => {
+                if client.opponent().is_none() && !evaluation.guessed {
                     return Err("Recevied evaluation before guess".into());
                 }
                 if evaluation.guessed {
                     log_info(format!(
-                        "Congratulations, you have won after {} guesses.",
-                        guess_count
+                        "Congratulations, you have won after {} guesses. Your score is {}.",
+                        guess_count, evaluation.score
                     ));
                     return Ok(());
                 }
 
-                log_info("You have not guessed.");
-
-                if let Some(hint) = evaluation.hint {
-                    log_info(format!("Hint provided by opponent: {}", hint));
+                match evaluation.hint {
+                    Some(hint) => log_info(format!("Hint provided by opponent: {}", hint)),
+                    None => log_info("You have not guessed."),
                 }
+                log_info(format!("Hints remaining: {}", evaluation.hints_remaining));
 
                 if get_wants_to_quit() {
                     log_info("Goodbye");
                     return Ok(());
                 }
 
-                send_guess(stream, player_id, asking_player_id.as_ref().unwrap())?;
-                guess_count += 1;
+                if send_guess(client)? {
+                    guess_count += 1;
+                }
+            }
+            GameEvent::GameOver(game_over) => {
+                log_info(format!(
+                    "You have run out of guesses. Player {} won. The answer was: {}",
+                    game_over.winner, game_over.answer
+                ));
+                return Ok(());
             }
-            Message::Riddle(riddle) => {
+            GameEvent::RiddleReceived(riddle) => {
                 if guess_count > 0 {
                     return Err("Received riddle for second time".into());
                 }
-                log_info(format!(
-                    "Player {} provided you with riddle. ",
-                    riddle.asking_player
-                ));
-                asking_player_id = Some(String::from(&riddle.asking_player));
+                if riddle.asking_player == HOUSE_PLAYER_ID {
+                    log_info(format!(
+                        "The house provided you with riddle: {}",
+                        riddle.message
+                    ));
+                } else {
+                    log_info(format!(
+                        "Player {} provided you with riddle. ",
+                        riddle.asking_player
+                    ));
+                }
                 if let Some(hint) = riddle.hint {
                     log_info(format!("Hint provided by opponent: {}", hint));
                 }
-                send_guess(stream, player_id, &riddle.asking_player)?;
-                guess_count += 1;
+                if !riddle.category.is_empty() {
+                    log_info(format!("Category: {}", riddle.category));
+                }
+                log_info(format!("Difficulty: {}", riddle.difficulty));
+                if riddle.max_guesses > 0 {
+                    log_info(format!("You have {} guesses.", riddle.max_guesses));
+                }
+                if send_guess(client)? {
+                    guess_count += 1;
+                }
             }
             _ => return Ok(()),
         }
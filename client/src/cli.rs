@@ -1,6 +1,14 @@
 use guessing_game::logging::{log_info, log_warn};
-use guessing_game::messages::{ConnectionType, Role};
-use std::io;
+use guessing_game::messages::{ChatMessage, ChatScope, ConnectionType, Difficulty, PlayerId, Role};
+use std::{env, io};
+
+/// Reads a `--flag=value` argument, falling back to an environment variable.
+pub fn get_setting(flag_prefix: &str, env_name: &str) -> Option<String> {
+    env::args()
+        .find_map(|arg| arg.strip_prefix(flag_prefix).map(str::to_owned))
+        .or_else(|| env::var(env_name).ok())
+        .filter(|value| !value.is_empty())
+}
 
 pub fn get_connection_type() -> ConnectionType {
     log_info("Please enter connection type. Tcp(t), Socket(s):");
@@ -22,7 +30,7 @@ pub fn get_connection_type() -> ConnectionType {
     }
 }
 
-pub fn get_role(available_opponents: &[String]) -> Role {
+pub fn get_role(available_opponents: &[PlayerId]) -> Role {
     if available_opponents.is_empty() {
         log_info("There are no available opponents. You can wait until someone challenges you.");
         return Role::GuessingPlayer;
@@ -40,6 +48,42 @@ pub fn get_role(available_opponents: &[String]) -> Role {
     Role::AskingPlayer
 }
 
+pub fn get_preferred_categories() -> Vec<String> {
+    get_user_input("Preferred riddle categories separated by commas (press enter for any):")
+        .split(',')
+        .map(|category| category.trim().to_owned())
+        .filter(|category| !category.is_empty())
+        .collect()
+}
+
+pub fn get_category() -> String {
+    get_user_input("Riddle category (press enter to skip):")
+}
+
+pub fn get_difficulty() -> Difficulty {
+    loop {
+        let input = get_user_input(
+            "Riddle difficulty. Easy(e), Medium(m), Hard(h) (press enter for medium):",
+        );
+        if input.is_empty() {
+            return Difficulty::default();
+        }
+
+        match input.parse() {
+            Ok(difficulty) => return difficulty,
+            Err(err) => log_warn(err),
+        }
+    }
+}
+
+pub fn get_wants_house_riddle() -> bool {
+    let decision = get_user_input(
+        "Write h to get a riddle from the house, otherwise press enter to wait for a challenge:",
+    );
+
+    decision == "h" || decision == "H"
+}
+
 pub fn get_user_input(message_for_user: &str) -> String {
     log_info(message_for_user);
     let mut input = String::new();
@@ -61,18 +105,46 @@ pub fn get_wants_to_quit() -> bool {
     false
 }
 
-pub fn get_hint() -> Option<String> {
-    let hint = get_user_input("You can provide a hint for user:");
-    let mut hint_option = None;
+pub fn get_max_guesses() -> u8 {
+    loop {
+        let input = get_user_input(
+            "Maximum number of guesses for this riddle (press enter for server default):",
+        );
+        if input.is_empty() {
+            return 0;
+        }
+
+        match input.parse() {
+            Ok(max_guesses) => return max_guesses,
+            Err(_) => log_warn("Please enter a number between 0 and 255."),
+        }
+    }
+}
+
+pub fn parse_chat_command(input: &str) -> Option<(ChatScope, String)> {
+    if let Some(text) = input.strip_prefix("/chat ") {
+        return Some((ChatScope::Game, text.trim().to_owned()));
+    }
 
-    if !hint.is_empty() {
-        hint_option = Some(hint);
+    if let Some(text) = input.strip_prefix("/lobby ") {
+        return Some((ChatScope::Lobby, text.trim().to_owned()));
     }
 
-    hint_option
+    None
 }
 
-pub fn get_opponent(available_opponents: &[String]) -> String {
+pub fn display_chat_message(chat_message: &ChatMessage) {
+    let scope = match chat_message.scope {
+        ChatScope::Lobby => "lobby",
+        ChatScope::Game => "game",
+    };
+    log_info(format!(
+        "[{} chat] {}: {}",
+        scope, chat_message.sender, chat_message.text
+    ));
+}
+
+pub fn get_opponent(available_opponents: &[PlayerId]) -> PlayerId {
     log_info("Here is list of your opponents, please write one:");
 
     for opponent in available_opponents {
@@ -80,16 +152,17 @@ pub fn get_opponent(available_opponents: &[String]) -> String {
     }
 
     loop {
-        let selected_opponent = get_user_input("");
-        if available_opponents.contains(&selected_opponent) {
-            return selected_opponent;
+        match get_user_input("").parse::<PlayerId>() {
+            Ok(selected_opponent) if available_opponents.contains(&selected_opponent) => {
+                return selected_opponent;
+            }
+            Ok(_) => log_info("Not opponent from the list. Try again:"),
+            Err(err) => log_warn(err),
         }
-
-        log_info("Not opponent from the list. Try again:");
     }
 }
 
-pub fn get_question(opponent_id: &str) -> String {
+pub fn get_question(opponent_id: PlayerId) -> String {
     let mut question = String::from("");
     while question.is_empty() {
         question = get_user_input(
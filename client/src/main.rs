@@ -1,108 +1,156 @@
 use behaviour::{handle_asking_role, handle_guessing_role, login};
-use cli::get_role;
+use cli::{display_chat_message, get_preferred_categories, get_role, get_wants_house_riddle};
+use guessing_game::client_core::GameClient;
 use guessing_game::logging::{log_error, log_info};
 use guessing_game::messages::{
-    ConnectionType, Message, Role, Streamable, TcpMessageStream, UnixMessageStream,
+    ConnectionType, Message, PlayerRole, RecordingStream, Role, Streamable, UnixMessageStream,
 };
+use std::env;
+use std::fs::File;
 use std::net::TcpStream;
 use std::os::unix::net::UnixStream;
+use std::process;
 
 use crate::cli::get_connection_type;
+use crate::tls::open_tcp_stream;
 
 mod behaviour;
 mod cli;
+mod script;
+mod tls;
+mod tui;
 
-fn process_client(mut stream: impl Streamable) {
-    match stream.read() {
-        Ok(message) => {
-            if let Message::GameStart = message {
-                log_info("game starts");
-            } else {
-                log_error("Initial message should be sent from server so game can start");
-                return;
-            }
+fn process_client(stream: impl Streamable) {
+    let mut client = GameClient::new(stream);
+    client.on_unsolicited(|message| match message {
+        Message::ChatMessage(chat_message) => {
+            display_chat_message(chat_message);
+            true
+        }
+        Message::Error(error_message) => {
+            log_error(error_message);
+            true
         }
+        _ => false,
+    });
+    match client.wait_for_start() {
+        Ok(()) => log_info("game starts"),
         Err(e) => {
             log_error(e);
+            log_error("Initial message should be sent from server so game can start");
             return;
         }
     }
-    let player_id = login(&mut stream);
-    if let Err(e) = player_id {
+    if let Err(e) = login(&mut client) {
         log_error(e);
         return;
     }
-    let player_id = player_id.unwrap();
-    let player_id = player_id.player_id;
+    log_info("Write /chat <text> to message your opponent or /lobby <text> to message the lobby.");
 
-    if let Err(e) = stream.write(&Message::RequestGuessingPlayers) {
-        log_error(e);
-        return;
+    let guessing_players = match client.list_opponents(None) {
+        Ok(guessing_players) => guessing_players,
+        Err(e) => {
+            log_error(e);
+            log_error("Did not receive message about guessing players ids");
+            return;
+        }
     };
 
-    let guessing_players = stream.read();
-
-    if let Err(e) = guessing_players {
-        log_error(e);
-        return;
-    }
-
-    let message = guessing_players.unwrap();
-    let mut guessing_players = Vec::new();
-
-    if let Message::PlayerList(player_list) = message {
-        guessing_players = player_list.opponent_ids;
-    } else {
-        log_error("Did not receive message about guessing players ids");
-    }
-
     match get_role(&guessing_players) {
         Role::GuessingPlayer => {
-            if let Err(err) = stream.write(&Message::RegisterPlayerRole(Role::GuessingPlayer)) {
+            let player_role = PlayerRole {
+                role: Role::GuessingPlayer,
+                categories: get_preferred_categories(),
+            };
+            if let Err(err) = client.register_role(player_role) {
                 log_error(err);
                 return;
             };
+            if get_wants_house_riddle() {
+                if let Err(err) = client.request_house_riddle() {
+                    log_error(err);
+                    return;
+                };
+            }
             log_info("Please wait until player provides you with riddle.");
-            if let Err(err) = handle_guessing_role(&mut stream, &player_id) {
+            if let Err(err) = handle_guessing_role(&mut client) {
                 log_error(err);
             }
-            let _ = stream.shutdown();
+            let _ = client.shutdown();
             return;
         }
         Role::AskingPlayer => {
-            if let Err(err) = handle_asking_role(&mut stream, &player_id, &guessing_players) {
+            if let Err(err) = handle_asking_role(&mut client, &guessing_players) {
                 log_error(err);
-                let _ = stream.shutdown();
+                let _ = client.shutdown();
                 return;
             }
         }
     }
 
-    if let Err(err) = stream.shutdown() {
+    if let Err(err) = client.shutdown() {
         log_error(err)
     }
 }
 
+fn run_client(stream: impl Streamable, use_tui: bool) {
+    if !use_tui {
+        process_client(stream);
+        return;
+    }
+
+    if let Err(err) = tui::run(stream) {
+        log_error(err);
+    }
+}
+
+fn start_client(stream: impl Streamable, use_tui: bool) {
+    let Some(path) = env::args().find_map(|arg| arg.strip_prefix("--record=").map(str::to_owned))
+    else {
+        run_client(stream, use_tui);
+        return;
+    };
+
+    match File::create(&path) {
+        Ok(recording) => run_client(RecordingStream::new(stream, recording), use_tui),
+        Err(e) => log_error(format!("Can not create recording {}: {}", path, e)),
+    }
+}
+
 fn main() {
+    if script::is_requested() {
+        if script::run().is_err() {
+            process::exit(1);
+        }
+        return;
+    }
+
     log_info("Welcome to guessing game");
+    let use_tui = env::args().any(|arg| arg == "--tui");
     match get_connection_type() {
         ConnectionType::Tcp => match TcpStream::connect("localhost:9000") {
-            Ok(stream) => {
-                let stream = TcpMessageStream { stream };
-                process_client(stream);
-            }
+            Ok(stream) => match open_tcp_stream(stream, use_tui) {
+                Ok(stream) => start_client(stream, use_tui),
+                Err(e) => log_error(e),
+            },
             Err(e) => {
                 log_error(e);
             }
         },
         ConnectionType::UnixSocket => match UnixStream::connect("/tmp/guessing_game") {
             Ok(stream) => {
-                let stream = UnixMessageStream { stream };
-                process_client(stream);
+                if let Err(e) = stream.set_nonblocking(use_tui) {
+                    log_error(e);
+                    return;
+                }
+                let stream = UnixMessageStream::from(stream);
+                start_client(stream, use_tui);
             }
             Err(e) => {
                 log_error(e);
             }
         },
+        ConnectionType::InMemory => log_error("In-memory connection can not reach the server"),
+        ConnectionType::WebSocket => log_error("WebSocket connections are for the browser client"),
     }
 }
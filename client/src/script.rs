@@ -0,0 +1,105 @@
+use crate::cli::get_setting;
+use crate::tls::open_tcp_stream;
+use action::Action;
+use event_stream::{emit, EventStream};
+use guessing_game::client_core::GameClient;
+use guessing_game::messages::{ConnectionType, RecordingStream, Streamable, UnixMessageStream};
+use serde_json::json;
+use std::env;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::net::TcpStream;
+use std::os::unix::net::UnixStream;
+
+mod action;
+mod event_stream;
+
+pub fn is_requested() -> bool {
+    env::args().any(|arg| arg == "--non-interactive")
+        || get_setting("--script=", "GUESSING_GAME_SCRIPT").is_some()
+}
+
+fn play_script(
+    stream: impl Streamable,
+    password: &str,
+    script: impl BufRead,
+) -> Result<(), String> {
+    let mut client = GameClient::new(EventStream::new(stream));
+    client.wait_for_start().map_err(|e| e.to_string())?;
+    client.login(password).map_err(|e| e.to_string())?;
+
+    for (line_number, line) in script.lines().enumerate() {
+        let line = line.map_err(|e| e.to_string())?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let action: Action = serde_json::from_str(line)
+            .map_err(|e| format!("Invalid action on line {}: {}", line_number + 1, e))?;
+        if matches!(action, Action::Quit) {
+            break;
+        }
+        action
+            .check_lengths()
+            .map_err(|e| format!("Invalid action on line {}: {}", line_number + 1, e))?;
+        action.apply(&mut client)?;
+    }
+
+    client.shutdown().map_err(|e| e.to_string())
+}
+
+fn run_script(stream: impl Streamable, password: &str, script: impl BufRead) -> Result<(), String> {
+    match get_setting("--record=", "GUESSING_GAME_RECORD") {
+        Some(path) => {
+            let recording = File::create(&path)
+                .map_err(|e| format!("Can not create recording {}: {}", path, e))?;
+            play_script(RecordingStream::new(stream, recording), password, script)
+        }
+        None => play_script(stream, password, script),
+    }
+}
+
+fn run_with_script(script: impl BufRead) -> Result<(), String> {
+    let password = get_setting("--password=", "GUESSING_GAME_PASSWORD")
+        .ok_or("Password must be provided as --password=<password> or GUESSING_GAME_PASSWORD")?;
+    let connection_type = match get_setting("--connection=", "GUESSING_GAME_CONNECTION").as_deref()
+    {
+        None | Some("tcp") => ConnectionType::Tcp,
+        Some("unix") => ConnectionType::UnixSocket,
+        Some(other) => return Err(format!("Unknown connection type {}", other)),
+    };
+    let address = get_setting("--address=", "GUESSING_GAME_ADDRESS");
+
+    match connection_type {
+        ConnectionType::Tcp => {
+            let address = address.as_deref().unwrap_or("localhost:9000");
+            let stream = TcpStream::connect(address).map_err(|e| e.to_string())?;
+            run_script(open_tcp_stream(stream, true)?, &password, script)
+        }
+        ConnectionType::UnixSocket => {
+            let address = address.as_deref().unwrap_or("/tmp/guessing_game");
+            let stream = UnixStream::connect(address).map_err(|e| e.to_string())?;
+            stream.set_nonblocking(true).map_err(|e| e.to_string())?;
+            run_script(UnixMessageStream::from(stream), &password, script)
+        }
+        ConnectionType::InMemory => Err("In-memory connection can not reach the server".into()),
+        ConnectionType::WebSocket => Err("WebSocket connections are for the browser client".into()),
+    }
+}
+
+/// Runs the client without prompts, reading JSON line actions from a script file or stdin.
+pub fn run() -> Result<(), String> {
+    let result = match get_setting("--script=", "GUESSING_GAME_SCRIPT") {
+        Some(path) => File::open(&path)
+            .map_err(|e| format!("Can not open {}: {}", path, e))
+            .and_then(|file| run_with_script(BufReader::new(file))),
+        None => run_with_script(io::stdin().lock()),
+    };
+
+    if let Err(err) = result.as_ref() {
+        emit(json!({ "event": "error", "message": err }));
+    }
+
+    result
+}
@@ -0,0 +1,204 @@
+use guessing_game::client_core::{GameClient, GameEvent};
+use guessing_game::messages::{
+    ChatScope, Difficulty, PlayerId, PlayerRole, Riddle, Role, Streamable,
+};
+use serde::Deserialize;
+use std::time::{Duration, Instant};
+use std::{thread, time};
+
+const DEFAULT_WAIT_TIMEOUT_MS: u64 = 10_000;
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ScriptRole {
+    Asking,
+    Guessing,
+}
+
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ScriptChatScope {
+    #[default]
+    Lobby,
+    Game,
+}
+
+/// One line of a client script, e.g. `{"action": "guess", "guess": "piano"}`.
+#[derive(Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Action {
+    ListOpponents {
+        #[serde(default)]
+        category: Option<String>,
+    },
+    Register {
+        role: ScriptRole,
+        #[serde(default)]
+        categories: Vec<String>,
+    },
+    Challenge {
+        asking_player: PlayerId,
+    },
+    RequestHouseRiddle,
+    SendRiddle {
+        guessing_player: PlayerId,
+        message: String,
+        #[serde(default)]
+        hint: Option<String>,
+        #[serde(default)]
+        category: String,
+        #[serde(default)]
+        difficulty: Option<String>,
+        #[serde(default)]
+        max_guesses: u8,
+    },
+    Guess {
+        guess: String,
+    },
+    RequestHint,
+    Evaluate {
+        guessed: bool,
+        #[serde(default)]
+        hint: Option<String>,
+    },
+    Chat {
+        #[serde(default)]
+        scope: ScriptChatScope,
+        text: String,
+    },
+    WaitFor {
+        event: String,
+        #[serde(default = "default_wait_timeout")]
+        timeout_ms: u64,
+    },
+    Sleep {
+        ms: u64,
+    },
+    Quit,
+}
+
+fn default_wait_timeout() -> u64 {
+    DEFAULT_WAIT_TIMEOUT_MS
+}
+
+pub fn event_name(event: &GameEvent) -> &'static str {
+    match event {
+        GameEvent::RiddleReceived(_) => "riddle_received",
+        GameEvent::GuessEvaluated { .. } => "guess_evaluated",
+        GameEvent::Evaluated(_) => "evaluated",
+        GameEvent::HintRequested => "hint_requested",
+        GameEvent::OpponentSelected(_) => "opponent_selected",
+        GameEvent::GameOver(_) => "game_over",
+        GameEvent::PlayerNotAvailable(_) => "player_not_available",
+        GameEvent::Chat(_) => "chat",
+        GameEvent::Error(_) => "rejected",
+        GameEvent::Unknown => "unknown",
+    }
+}
+
+fn wait_for(
+    client: &mut GameClient<impl Streamable>,
+    expected: &str,
+    timeout: Duration,
+) -> Result<(), String> {
+    let started = Instant::now();
+
+    while started.elapsed() < timeout {
+        match client.poll_event().map_err(|e| e.to_string())? {
+            Some(event) if event_name(&event) == expected => return Ok(()),
+            Some(_) => {}
+            None => thread::sleep(time::Duration::from_millis(1)),
+        }
+    }
+
+    Err(format!("Timed out waiting for {}", expected))
+}
+
+impl Action {
+    /// Refuses texts the binary format can not carry, they are limited to 255 bytes.
+    pub fn check_lengths(&self) -> Result<(), String> {
+        let texts = match self {
+            Action::SendRiddle {
+                message,
+                hint,
+                category,
+                ..
+            } => vec![("message", message), ("category", category)]
+                .into_iter()
+                .chain(hint.iter().map(|hint| ("hint", hint)))
+                .collect(),
+            Action::Guess { guess } => vec![("guess", guess)],
+            _ => vec![],
+        };
+
+        match texts.iter().find(|(_, text)| text.len() > u8::MAX as usize) {
+            Some((field, _)) => Err(format!("{} is longer than {} bytes", field, u8::MAX)),
+            None => Ok(()),
+        }
+    }
+
+    pub fn apply(self, client: &mut GameClient<impl Streamable>) -> Result<(), String> {
+        match self {
+            Action::ListOpponents { category } => {
+                client.list_opponents(category).map_err(|e| e.to_string())?;
+            }
+            Action::Register { role, categories } => {
+                let role = match role {
+                    ScriptRole::Asking => Role::AskingPlayer,
+                    ScriptRole::Guessing => Role::GuessingPlayer,
+                };
+                client
+                    .register_role(PlayerRole { role, categories })
+                    .map_err(|e| e.to_string())?;
+            }
+            Action::Challenge { asking_player } => {
+                client.challenge(asking_player).map_err(|e| e.to_string())?;
+            }
+            Action::RequestHouseRiddle => {
+                client.request_house_riddle().map_err(|e| e.to_string())?;
+            }
+            Action::SendRiddle {
+                guessing_player,
+                message,
+                hint,
+                category,
+                difficulty,
+                max_guesses,
+            } => {
+                let difficulty = match difficulty {
+                    Some(difficulty) => difficulty.parse::<Difficulty>()?,
+                    None => Difficulty::default(),
+                };
+                let riddle = Riddle {
+                    guessing_player,
+                    message,
+                    hint,
+                    max_guesses,
+                    category,
+                    difficulty,
+                    ..Default::default()
+                };
+                client.send_riddle(riddle).map_err(|e| e.to_string())?;
+            }
+            Action::Guess { guess } => client.guess(&guess).map_err(|e| e.to_string())?,
+            Action::RequestHint => client.request_hint().map_err(|e| e.to_string())?,
+            Action::Evaluate { guessed, hint } => {
+                client.evaluate(guessed, hint).map_err(|e| e.to_string())?;
+            }
+            Action::Chat { scope, text } => {
+                let scope = match scope {
+                    ScriptChatScope::Lobby => ChatScope::Lobby,
+                    ScriptChatScope::Game => ChatScope::Game,
+                };
+                client.chat(scope, &text).map_err(|e| e.to_string())?;
+            }
+            Action::WaitFor { event, timeout_ms } => {
+                wait_for(client, &event, Duration::from_millis(timeout_ms))?;
+            }
+            Action::Sleep { ms } => thread::sleep(Duration::from_millis(ms)),
+            Action::Quit => {}
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,56 @@
+use guessing_game::messages::{Frame, Message, MessageError, Streamable};
+use serde_json::{json, Value};
+use std::io::{self, Write};
+
+pub fn emit(event: Value) {
+    let mut stdout = io::stdout().lock();
+    let _ = writeln!(stdout, "{}", event);
+    let _ = stdout.flush();
+}
+
+pub fn message_to_json(message: &Message) -> Value {
+    match message {
+        // keeps passwords out of the event output
+        Message::Password(_) => json!({ "type": "password" }),
+        message => serde_json::to_value(message).unwrap_or(Value::Null),
+    }
+}
+
+/// Emits every received message as a JSON line on stdout.
+pub struct EventStream<S: Streamable> {
+    stream: S,
+}
+
+impl<S: Streamable> EventStream<S> {
+    pub fn new(stream: S) -> Self {
+        EventStream { stream }
+    }
+}
+
+impl<S: Streamable> Streamable for EventStream<S> {
+    fn read(&mut self) -> Result<Message, MessageError> {
+        let message = self.stream.read()?;
+        emit(json!({ "event": "received", "message": message_to_json(&message) }));
+
+        Ok(message)
+    }
+
+    fn write(&mut self, message: &Message) -> Result<(), MessageError> {
+        self.stream.write(message)
+    }
+
+    fn shutdown(&mut self) -> Result<(), MessageError> {
+        self.stream.shutdown()
+    }
+
+    fn read_frame(&mut self) -> Result<Frame, MessageError> {
+        let frame = self.stream.read_frame()?;
+        emit(json!({ "event": "received", "message": message_to_json(&frame.message) }));
+
+        Ok(frame)
+    }
+
+    fn write_frame(&mut self, frame: &Frame) -> Result<(), MessageError> {
+        self.stream.write_frame(frame)
+    }
+}
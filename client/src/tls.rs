@@ -0,0 +1,40 @@
+use crate::cli::get_setting;
+use guessing_game::messages::{ClientTls, Streamable, TcpMessageStream};
+use std::net::TcpStream;
+use std::path::Path;
+
+fn get_client_tls() -> Result<Option<ClientTls>, String> {
+    let server_name = get_setting("--tls-server-name=", "GUESSING_GAME_TLS_SERVER_NAME")
+        .unwrap_or_else(|| "localhost".into());
+
+    if let Some(fingerprint) = get_setting("--tls-pin=", "GUESSING_GAME_TLS_PIN") {
+        return ClientTls::pinned(&server_name, &fingerprint).map(Some);
+    }
+
+    match get_setting("--tls-ca=", "GUESSING_GAME_TLS_CA") {
+        Some(ca_path) => ClientTls::with_ca_file(&server_name, Path::new(&ca_path)).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Wraps a connected TCP stream, using TLS when `--tls-pin` or `--tls-ca` is set.
+pub fn open_tcp_stream(
+    stream: TcpStream,
+    nonblocking: bool,
+) -> Result<Box<dyn Streamable>, String> {
+    let Some(client_tls) = get_client_tls()? else {
+        stream
+            .set_nonblocking(nonblocking)
+            .map_err(|e| e.to_string())?;
+        return Ok(Box::new(TcpMessageStream::from(stream)));
+    };
+
+    let tls_stream = client_tls.connect(stream)?;
+    tls_stream
+        .get_ref()
+        .sock
+        .set_nonblocking(nonblocking)
+        .map_err(|e| e.to_string())?;
+
+    Ok(Box::new(tls_stream))
+}
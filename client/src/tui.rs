@@ -0,0 +1,75 @@
+use app::App;
+use guessing_game::client_core::GameClient;
+use guessing_game::messages::Streamable;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use std::time::{Duration, Instant};
+
+mod app;
+mod view;
+
+const INPUT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+const OPPONENTS_REFRESH_INTERVAL: Duration = Duration::from_secs(3);
+
+pub fn run(stream: impl Streamable) -> Result<(), String> {
+    let mut client = GameClient::new(stream);
+    client.wait_for_start().map_err(|e| e.to_string())?;
+
+    let mut terminal = ratatui::init();
+    let result = run_app(&mut terminal, &mut client);
+    ratatui::restore();
+    let _ = client.shutdown();
+
+    result
+}
+
+fn run_app(
+    terminal: &mut ratatui::DefaultTerminal,
+    client: &mut GameClient<impl Streamable>,
+) -> Result<(), String> {
+    let mut app = App::new();
+    let mut last_refresh = Instant::now();
+
+    while !app.should_quit {
+        while let Some(game_event) = client.poll_event().map_err(|e| e.to_string())? {
+            let result = app.handle_event(client, game_event);
+            app.show_error(result).map_err(|e| e.to_string())?;
+        }
+
+        if matches!(app.phase, app::Phase::Lobby)
+            && last_refresh.elapsed() >= OPPONENTS_REFRESH_INTERVAL
+        {
+            let result = app.refresh_opponents(client);
+            app.show_error(result).map_err(|e| e.to_string())?;
+            last_refresh = Instant::now();
+        }
+
+        terminal
+            .draw(|frame| view::draw(frame, &app, client))
+            .map_err(|e| e.to_string())?;
+
+        if !event::poll(INPUT_POLL_INTERVAL).map_err(|e| e.to_string())? {
+            continue;
+        }
+        let Event::Key(key) = event::read().map_err(|e| e.to_string())? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+
+        match key.code {
+            KeyCode::Esc => app.should_quit = true,
+            KeyCode::Enter => {
+                let result = app.submit(client);
+                app.show_error(result).map_err(|e| e.to_string())?;
+            }
+            KeyCode::Backspace => {
+                app.input.pop();
+            }
+            KeyCode::Char(character) => app.input.push(character),
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
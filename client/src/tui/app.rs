@@ -0,0 +1,411 @@
+use crate::cli::parse_chat_command;
+use guessing_game::client_core::{ClientError, GameClient, GameEvent};
+use guessing_game::messages::{
+    ChatMessage, ChatScope, Difficulty, PlayerId, PlayerRole, Riddle, Role, Streamable,
+    HOUSE_PLAYER_ID, MAX_CHAT_MESSAGE_LENGTH,
+};
+
+pub enum Phase {
+    Login,
+    Lobby,
+    ChoosingOpponent,
+    WritingRiddle,
+    WritingHint,
+    WritingCategory,
+    WritingDifficulty,
+    AwaitingGuess,
+    Evaluating,
+    WaitingForRiddle,
+    Guessing,
+}
+
+pub struct GuessEntry {
+    pub guess: String,
+    pub guessed: Option<bool>,
+}
+
+pub struct App {
+    pub phase: Phase,
+    pub input: String,
+    pub opponents: Vec<PlayerId>,
+    pub riddle: Option<Riddle>,
+    pub guesses: Vec<GuessEntry>,
+    pub hints: Vec<String>,
+    pub hints_remaining: Option<u8>,
+    pub chat: Vec<String>,
+    pub status: String,
+    pub should_quit: bool,
+}
+
+impl App {
+    pub fn new() -> Self {
+        App {
+            phase: Phase::Login,
+            input: String::new(),
+            opponents: Vec::new(),
+            riddle: None,
+            guesses: Vec::new(),
+            hints: Vec::new(),
+            hints_remaining: None,
+            chat: Vec::new(),
+            status: "Connected to server".into(),
+            should_quit: false,
+        }
+    }
+
+    pub fn prompt(&self) -> &'static str {
+        match self.phase {
+            Phase::Login => "Server password",
+            Phase::Lobby => "/ask, /guess [categories], /house, /refresh or /quit",
+            Phase::ChoosingOpponent => "Opponent number or id",
+            Phase::WritingRiddle => "Riddle answer for your opponent",
+            Phase::WritingHint => "Hint for opponent (enter to skip)",
+            Phase::WritingCategory => "Riddle category (enter to skip)",
+            Phase::WritingDifficulty => "Difficulty e/m/h (enter for medium)",
+            Phase::AwaitingGuess => "Waiting for opponent guess, /chat <text> to talk",
+            Phase::Evaluating => "Hint for opponent (enter to skip)",
+            Phase::WaitingForRiddle => "Waiting for riddle, /lobby <text> to talk",
+            Phase::Guessing => "Your guess, or /hint to ask for a hint",
+        }
+    }
+
+    pub fn is_secret_input(&self) -> bool {
+        matches!(self.phase, Phase::Login)
+    }
+
+    /// Shows a failed request in the status line, only transport errors are passed on and
+    /// end the TUI.
+    pub fn show_error(&mut self, result: Result<(), ClientError>) -> Result<(), ClientError> {
+        match result {
+            Err(ClientError::Message(err)) => Err(ClientError::Message(err)),
+            Err(err) => {
+                self.status = err.to_string();
+                Ok(())
+            }
+            Ok(()) => Ok(()),
+        }
+    }
+
+    pub fn refresh_opponents(
+        &mut self,
+        client: &mut GameClient<impl Streamable>,
+    ) -> Result<(), ClientError> {
+        let player_id = client.player_id();
+        self.opponents = client.list_opponents(None)?;
+        self.opponents
+            .retain(|opponent| Some(*opponent) != player_id);
+
+        Ok(())
+    }
+
+    pub fn handle_event(
+        &mut self,
+        client: &mut GameClient<impl Streamable>,
+        event: GameEvent,
+    ) -> Result<(), ClientError> {
+        match event {
+            GameEvent::Chat(chat_message) => self.push_chat(&chat_message),
+            GameEvent::RiddleReceived(riddle) => {
+                self.status = match riddle.asking_player {
+                    HOUSE_PLAYER_ID => "The house provided you with a riddle".into(),
+                    asking_player => format!("Player {} provided you with a riddle", asking_player),
+                };
+                self.hints.extend(riddle.hint.clone());
+                self.riddle = Some(riddle);
+                self.phase = Phase::Guessing;
+            }
+            GameEvent::Evaluated(evaluation) => {
+                if let Some(entry) = self
+                    .guesses
+                    .iter_mut()
+                    .rev()
+                    .find(|entry| entry.guessed.is_none())
+                {
+                    entry.guessed = Some(evaluation.guessed);
+                }
+                self.hints_remaining = Some(evaluation.hints_remaining);
+
+                if evaluation.guessed {
+                    self.status = format!(
+                        "Congratulations, you have won after {} guesses. Your score is {}.",
+                        self.guesses.len(),
+                        evaluation.score
+                    );
+                    self.return_to_lobby(client)?;
+                    return Ok(());
+                }
+
+                match evaluation.hint {
+                    Some(hint) => {
+                        self.status = "Opponent provided a hint".into();
+                        self.hints.push(hint);
+                    }
+                    None => self.status = "You have not guessed".into(),
+                }
+            }
+            GameEvent::GuessEvaluated { guessed } => {
+                self.guesses.push(GuessEntry {
+                    guess: format!("Guess {}", self.guesses.len() + 1),
+                    guessed: Some(guessed),
+                });
+
+                if guessed {
+                    client.evaluate(true, None)?;
+                    self.status = format!(
+                        "Opponent has guessed after {} tries. You lost.",
+                        self.guesses.len()
+                    );
+                    self.return_to_lobby(client)?;
+                    return Ok(());
+                }
+
+                self.status = "Opponent has not guessed, you can provide a hint".into();
+                self.phase = Phase::Evaluating;
+            }
+            GameEvent::HintRequested => {
+                self.status = "Opponent has requested a hint".into();
+                self.phase = Phase::Evaluating;
+            }
+            GameEvent::OpponentSelected(guessing_player) => {
+                self.status = format!("Player {} wants to guess your riddle", guessing_player);
+            }
+            GameEvent::GameOver(game_over) => {
+                self.status = match client.role() {
+                    Some(Role::AskingPlayer) => format!(
+                        "Opponent has run out of guesses. You won. The answer was: {}",
+                        game_over.answer
+                    ),
+                    _ => format!(
+                        "You have run out of guesses. Player {} won. The answer was: {}",
+                        game_over.winner, game_over.answer
+                    ),
+                };
+                self.return_to_lobby(client)?;
+            }
+            GameEvent::PlayerNotAvailable(id) if id == HOUSE_PLAYER_ID => {
+                self.status = "The house has no riddles available".into();
+                self.return_to_lobby(client)?;
+            }
+            GameEvent::PlayerNotAvailable(id) => {
+                self.status = format!("Player {} is not available", id);
+                if matches!(self.phase, Phase::Lobby | Phase::ChoosingOpponent) {
+                    self.refresh_opponents(client)?;
+                } else {
+                    self.return_to_lobby(client)?;
+                }
+            }
+            GameEvent::Error(error_message) => self.status = error_message.to_string(),
+            GameEvent::Unknown => {}
+        }
+
+        Ok(())
+    }
+
+    pub fn submit(&mut self, client: &mut GameClient<impl Streamable>) -> Result<(), ClientError> {
+        let input = self.input.trim().to_owned();
+        self.input.clear();
+
+        if !matches!(self.phase, Phase::Login) {
+            if let Some((scope, text)) = parse_chat_command(&input) {
+                return self.send_chat(client, scope, text);
+            }
+        }
+
+        let is_game_text = matches!(
+            self.phase,
+            Phase::WritingRiddle
+                | Phase::WritingHint
+                | Phase::WritingCategory
+                | Phase::Evaluating
+                | Phase::Guessing
+        );
+        if is_game_text && input.len() > u8::MAX as usize {
+            self.status = format!("Text can have at most {} characters", u8::MAX);
+            return Ok(());
+        }
+
+        match self.phase {
+            Phase::Login => {
+                client.login(&input)?;
+                self.status = "Logged in".into();
+                self.refresh_opponents(client)?;
+                self.phase = Phase::Lobby;
+            }
+            Phase::Lobby => self.handle_lobby_command(client, &input)?,
+            Phase::ChoosingOpponent => {
+                let opponent = input
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|number| self.opponents.get(number.wrapping_sub(1)))
+                    .or_else(|| self.opponents.iter().find(|id| **id == input.as_str()));
+                match opponent {
+                    Some(opponent) => {
+                        self.riddle = Some(Riddle {
+                            guessing_player: *opponent,
+                            ..Default::default()
+                        });
+                        self.phase = Phase::WritingRiddle;
+                    }
+                    None => self.status = "Not opponent from the list".into(),
+                }
+            }
+            Phase::WritingRiddle if input.is_empty() => {
+                self.status = "Riddle can not be empty".into();
+            }
+            Phase::WritingRiddle => {
+                self.edit_riddle(|riddle| riddle.message = input);
+                self.phase = Phase::WritingHint;
+            }
+            Phase::WritingHint => {
+                let hint = Some(input).filter(|hint| !hint.is_empty());
+                self.hints.extend(hint.clone());
+                self.edit_riddle(|riddle| riddle.hint = hint);
+                self.phase = Phase::WritingCategory;
+            }
+            Phase::WritingCategory => {
+                self.edit_riddle(|riddle| riddle.category = input);
+                self.phase = Phase::WritingDifficulty;
+            }
+            Phase::WritingDifficulty => {
+                let difficulty = if input.is_empty() {
+                    Default::default()
+                } else {
+                    match input.parse::<Difficulty>() {
+                        Ok(difficulty) => difficulty,
+                        Err(err) => {
+                            self.status = err.to_string();
+                            return Ok(());
+                        }
+                    }
+                };
+                self.edit_riddle(|riddle| riddle.difficulty = difficulty);
+
+                if let Some(riddle) = self.riddle.clone() {
+                    client.send_riddle(riddle)?;
+                    self.status = "Riddle sent, waiting for opponent guess".into();
+                }
+                self.phase = Phase::AwaitingGuess;
+            }
+            Phase::Evaluating => {
+                let hint = Some(input).filter(|hint| !hint.is_empty());
+                self.hints.extend(hint.clone());
+                client.evaluate(false, hint)?;
+                self.phase = Phase::AwaitingGuess;
+            }
+            Phase::Guessing if input == "/hint" => {
+                client.request_hint()?;
+                self.status = "Waiting for hint".into();
+            }
+            Phase::Guessing if !input.is_empty() => {
+                client.guess(&input)?;
+                self.guesses.push(GuessEntry {
+                    guess: input,
+                    guessed: None,
+                });
+                self.status = "Waiting for evaluation".into();
+            }
+            Phase::Guessing | Phase::AwaitingGuess | Phase::WaitingForRiddle => {}
+        }
+
+        Ok(())
+    }
+
+    fn handle_lobby_command(
+        &mut self,
+        client: &mut GameClient<impl Streamable>,
+        input: &str,
+    ) -> Result<(), ClientError> {
+        let (command, arguments) = input.split_once(' ').unwrap_or((input, ""));
+
+        match command {
+            "/ask" => {
+                client.register_role(Role::AskingPlayer.into())?;
+                self.refresh_opponents(client)?;
+                if self.opponents.is_empty() {
+                    self.status = "There are no available opponents".into();
+                    return Ok(());
+                }
+                self.reset_game();
+                self.phase = Phase::ChoosingOpponent;
+            }
+            "/guess" | "/house" => {
+                let player_role = PlayerRole {
+                    role: Role::GuessingPlayer,
+                    categories: arguments
+                        .split(',')
+                        .map(|category| category.trim().to_owned())
+                        .filter(|category| !category.is_empty())
+                        .collect(),
+                };
+                client.register_role(player_role)?;
+                if command == "/house" {
+                    client.request_house_riddle()?;
+                }
+                self.reset_game();
+                self.status = "Please wait until player provides you with riddle".into();
+                self.phase = Phase::WaitingForRiddle;
+            }
+            "/refresh" => self.refresh_opponents(client)?,
+            "/quit" => self.should_quit = true,
+            _ => self.status = format!("Unknown command {}", command),
+        }
+
+        Ok(())
+    }
+
+    fn send_chat(
+        &mut self,
+        client: &mut GameClient<impl Streamable>,
+        scope: ChatScope,
+        text: String,
+    ) -> Result<(), ClientError> {
+        if text.is_empty() || text.len() > MAX_CHAT_MESSAGE_LENGTH {
+            self.status = format!(
+                "Chat message must have between 1 and {} characters",
+                MAX_CHAT_MESSAGE_LENGTH
+            );
+            return Ok(());
+        }
+
+        client.chat(scope, &text)?;
+        self.push_chat(&ChatMessage {
+            sender: client.player_id().unwrap_or_default(),
+            scope,
+            text,
+        });
+
+        Ok(())
+    }
+
+    fn push_chat(&mut self, chat_message: &ChatMessage) {
+        let scope = match chat_message.scope {
+            ChatScope::Lobby => "lobby",
+            ChatScope::Game => "game",
+        };
+        self.chat.push(format!(
+            "[{}] {}: {}",
+            scope, chat_message.sender, chat_message.text
+        ));
+    }
+
+    fn edit_riddle(&mut self, edit: impl FnOnce(&mut Riddle)) {
+        if let Some(riddle) = self.riddle.as_mut() {
+            edit(riddle);
+        }
+    }
+
+    fn reset_game(&mut self) {
+        self.riddle = None;
+        self.guesses.clear();
+        self.hints.clear();
+        self.hints_remaining = None;
+    }
+
+    fn return_to_lobby(
+        &mut self,
+        client: &mut GameClient<impl Streamable>,
+    ) -> Result<(), ClientError> {
+        self.phase = Phase::Lobby;
+        self.refresh_opponents(client)
+    }
+}
@@ -0,0 +1,141 @@
+use super::app::App;
+use guessing_game::client_core::GameClient;
+use guessing_game::messages::{PlayerId, Role, Streamable, HOUSE_PLAYER_ID};
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, Paragraph, Wrap};
+use ratatui::Frame;
+
+pub fn draw(frame: &mut Frame, app: &App, client: &GameClient<impl Streamable>) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(10),
+            Constraint::Length(8),
+            Constraint::Length(3),
+            Constraint::Length(1),
+        ])
+        .split(frame.area());
+    let panes = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(30), Constraint::Percentage(70)])
+        .split(rows[0]);
+
+    let opponents = app
+        .opponents
+        .iter()
+        .enumerate()
+        .map(|(index, id)| ListItem::new(format!("{}. {}", index + 1, id)))
+        .collect::<Vec<ListItem>>();
+    frame.render_widget(
+        List::new(opponents).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Lobby - guessing players"),
+        ),
+        panes[0],
+    );
+
+    frame.render_widget(
+        Paragraph::new(game_lines(app, client.role()))
+            .wrap(Wrap { trim: false })
+            .block(Block::default().borders(Borders::ALL).title("Game")),
+        panes[1],
+    );
+
+    let chat_height = rows[1].height.saturating_sub(2) as usize;
+    let chat = app
+        .chat
+        .iter()
+        .skip(app.chat.len().saturating_sub(chat_height))
+        .map(|line| Line::from(line.as_str()))
+        .collect::<Vec<Line>>();
+    frame.render_widget(
+        Paragraph::new(chat).block(Block::default().borders(Borders::ALL).title("Chat")),
+        rows[1],
+    );
+
+    let input = if app.is_secret_input() {
+        "*".repeat(app.input.chars().count())
+    } else {
+        app.input.clone()
+    };
+    frame.render_widget(
+        Paragraph::new(input.as_str())
+            .block(Block::default().borders(Borders::ALL).title(app.prompt())),
+        rows[2],
+    );
+    frame.set_cursor_position((rows[2].x + 1 + input.chars().count() as u16, rows[2].y + 1));
+
+    let status = format!(
+        " player: {} | opponent: {} | {}",
+        client.player_id().as_ref().map_or("-", PlayerId::as_str),
+        client.opponent().as_ref().map_or("-", PlayerId::as_str),
+        app.status
+    );
+    frame.render_widget(
+        Paragraph::new(status).style(Style::default().bg(Color::Blue).fg(Color::White)),
+        rows[3],
+    );
+}
+
+fn game_lines(app: &App, role: Option<Role>) -> Vec<Line<'_>> {
+    let mut lines = Vec::new();
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+
+    let Some(riddle) = app.riddle.as_ref() else {
+        lines.push(Line::from("No game in progress."));
+        return lines;
+    };
+
+    match role {
+        Some(Role::AskingPlayer) => lines.push(Line::from(vec![
+            Span::styled("Your answer: ", bold),
+            Span::raw(riddle.message.as_str()),
+        ])),
+        _ if riddle.asking_player == HOUSE_PLAYER_ID => lines.push(Line::from(vec![
+            Span::styled("Riddle: ", bold),
+            Span::raw(riddle.message.as_str()),
+        ])),
+        _ => lines.push(Line::from(vec![
+            Span::styled("Riddle from: ", bold),
+            Span::raw(riddle.asking_player.as_str()),
+        ])),
+    }
+    if !riddle.category.is_empty() {
+        lines.push(Line::from(format!("Category: {}", riddle.category)));
+    }
+    lines.push(Line::from(format!("Difficulty: {}", riddle.difficulty)));
+    if riddle.max_guesses > 0 {
+        lines.push(Line::from(format!("Max guesses: {}", riddle.max_guesses)));
+    }
+
+    lines.push(Line::default());
+    let hints_title = match app.hints_remaining {
+        Some(hints_remaining) => format!("Hints ({} remaining):", hints_remaining),
+        None => "Hints:".into(),
+    };
+    lines.push(Line::styled(hints_title, bold));
+    lines.extend(
+        app.hints
+            .iter()
+            .map(|hint| Line::from(format!("  {}", hint))),
+    );
+
+    lines.push(Line::default());
+    lines.push(Line::styled("Guesses:", bold));
+    lines.extend(app.guesses.iter().map(|entry| {
+        let (mark, color) = match entry.guessed {
+            Some(true) => ("correct", Color::Green),
+            Some(false) => ("wrong", Color::Red),
+            None => ("waiting", Color::Yellow),
+        };
+        Line::from(vec![
+            Span::raw(format!("  {} ", entry.guess)),
+            Span::styled(format!("[{}]", mark), Style::default().fg(color)),
+        ])
+    }));
+
+    lines
+}
@@ -0,0 +1,43 @@
+use messages::{ErrorMessage, MessageError};
+use std::fmt;
+use std::fmt::{Debug, Display};
+
+pub enum ClientError {
+    Message(MessageError),
+    UnexpectedMessage(&'static str),
+    /// Server answered with an error message instead of the expected response.
+    Rejected(ErrorMessage),
+    NotLoggedIn,
+    NoOpponent,
+    /// Text or list does not fit the 255 byte or item limit of the binary format.
+    TooLong,
+}
+
+impl From<MessageError> for ClientError {
+    fn from(err: MessageError) -> Self {
+        Self::Message(err)
+    }
+}
+
+impl Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Message(err) => write!(f, "{}", err),
+            Self::UnexpectedMessage(expected) => {
+                write!(f, "Server Error. Expected {} message", expected)
+            }
+            Self::Rejected(error_message) => {
+                write!(f, "Server rejected request. {}", error_message)
+            }
+            Self::NotLoggedIn => write!(f, "Player is not logged in"),
+            Self::NoOpponent => write!(f, "Player has no opponent"),
+            Self::TooLong => write!(f, "Text or list is longer than {}", u8::MAX),
+        }
+    }
+}
+
+impl Debug for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
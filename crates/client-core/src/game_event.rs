@@ -0,0 +1,42 @@
+use messages::{ChatMessage, ErrorMessage, Evaluation, GameOver, Message, PlayerId, Riddle};
+
+#[derive(Debug)]
+pub enum GameEvent {
+    /// Riddle sent to the guessing player by the asking player or the house.
+    RiddleReceived(Riddle),
+    /// Server evaluation of the opponent's guess, received by the asking player.
+    GuessEvaluated {
+        guessed: bool,
+    },
+    /// Evaluation of the last guess, received by the guessing player.
+    Evaluated(Evaluation),
+    HintRequested,
+    OpponentSelected(PlayerId),
+    GameOver(GameOver),
+    PlayerNotAvailable(PlayerId),
+    Chat(ChatMessage),
+    /// Server rejected a message, the connection stays open unless a read error follows.
+    Error(ErrorMessage),
+    Unknown,
+}
+
+impl GameEvent {
+    pub fn from_message(message: Message, is_asking_player: bool) -> Self {
+        match message {
+            Message::Riddle(riddle) => Self::RiddleReceived(riddle),
+            Message::Evaluation(evaluation) if is_asking_player => Self::GuessEvaluated {
+                guessed: evaluation.guessed,
+            },
+            Message::Evaluation(evaluation) => Self::Evaluated(evaluation),
+            Message::RequestHint => Self::HintRequested,
+            Message::OpponentSelected(opponent_selected) => {
+                Self::OpponentSelected(opponent_selected.guessing_player)
+            }
+            Message::GameOver(game_over) => Self::GameOver(game_over),
+            Message::PlayerNotAvailable(id) => Self::PlayerNotAvailable(id),
+            Message::ChatMessage(chat_message) => Self::Chat(chat_message),
+            Message::Error(error_message) => Self::Error(error_message),
+            _ => Self::Unknown,
+        }
+    }
+}
@@ -0,0 +1,535 @@
+mod client_error;
+mod game_event;
+
+pub use client_error::ClientError;
+pub use game_event::GameEvent;
+
+use messages::{
+    fits_binary_format, ChatMessage, ChatScope, Evaluation, Frame, Message, MessageError,
+    OpponentSelected, Password, PlayerId, PlayerRole, Riddle, Role, Streamable,
+};
+use std::collections::VecDeque;
+use std::{thread, time};
+
+type UnsolicitedHandler = Box<dyn FnMut(&Message) -> bool + Send>;
+
+pub struct GameClient<S: Streamable> {
+    stream: S,
+    player_id: Option<PlayerId>,
+    role: Option<Role>,
+    opponent: Option<PlayerId>,
+    pending: VecDeque<Message>,
+    next_request_id: u32,
+    unsolicited_handlers: Vec<UnsolicitedHandler>,
+}
+
+impl<S: Streamable> GameClient<S> {
+    pub fn new(stream: S) -> Self {
+        GameClient {
+            stream,
+            player_id: None,
+            role: None,
+            opponent: None,
+            pending: VecDeque::new(),
+            next_request_id: 1,
+            unsolicited_handlers: Vec::new(),
+        }
+    }
+
+    pub fn player_id(&self) -> Option<PlayerId> {
+        self.player_id
+    }
+
+    pub fn opponent(&self) -> Option<PlayerId> {
+        self.opponent
+    }
+
+    pub fn role(&self) -> Option<Role> {
+        self.role
+    }
+
+    pub fn wait_for_start(&mut self) -> Result<(), ClientError> {
+        match self.read_message()? {
+            Message::GameStart => Ok(()),
+            Message::Error(error_message) => Err(ClientError::Rejected(error_message)),
+            _ => Err(ClientError::UnexpectedMessage("game start")),
+        }
+    }
+
+    /// Registers a handler for messages arriving while `request` waits for its response.
+    ///
+    /// Handlers are called in registration order until one returns `true`, messages no
+    /// handler takes are returned by `next_event` later.
+    pub fn on_unsolicited(&mut self, handler: impl FnMut(&Message) -> bool + Send + 'static) {
+        self.unsolicited_handlers.push(Box::new(handler));
+    }
+
+    /// Sends `message` with a new request id and waits for the response echoing it.
+    ///
+    /// Servers that do not echo request ids are supported by taking the first message
+    /// without one that `is_response` accepts. An error message answering the request
+    /// is returned as `ClientError::Rejected`.
+    pub fn request(
+        &mut self,
+        message: Message,
+        is_response: impl Fn(&Message) -> bool,
+        expected: &'static str,
+    ) -> Result<Message, ClientError> {
+        if !fits_binary_format(&message) {
+            return Err(ClientError::TooLong);
+        }
+        let request_id = self.next_request_id;
+        self.next_request_id = self.next_request_id.wrapping_add(1);
+        self.stream.write_frame(&Frame {
+            request_id: Some(request_id),
+            message,
+        })?;
+
+        loop {
+            let frame = self.read_frame()?;
+            let is_match = match frame.request_id {
+                Some(id) => id == request_id,
+                None => is_response(&frame.message),
+            };
+            match frame.message {
+                Message::Error(error_message) if is_match => {
+                    return Err(ClientError::Rejected(error_message))
+                }
+                message if is_match => return Ok(message),
+                Message::Unknown => return Err(ClientError::UnexpectedMessage(expected)),
+                message => self.dispatch_unsolicited(message),
+            }
+        }
+    }
+
+    pub fn login(&mut self, password: &str) -> Result<PlayerId, ClientError> {
+        let Message::PlayerId(player_id) = self.request(
+            Message::Password(Password {
+                password: password.into(),
+            }),
+            |message| matches!(message, Message::PlayerId(_)),
+            "player id",
+        )?
+        else {
+            return Err(ClientError::UnexpectedMessage("player id"));
+        };
+        self.player_id = Some(player_id);
+
+        Ok(player_id)
+    }
+
+    pub fn register_role(&mut self, player_role: PlayerRole) -> Result<(), ClientError> {
+        let role = player_role.role;
+        self.stream
+            .write(&Message::RegisterPlayerRole(player_role))?;
+        self.role = Some(role);
+
+        Ok(())
+    }
+
+    pub fn list_opponents(
+        &mut self,
+        category: Option<String>,
+    ) -> Result<Vec<PlayerId>, ClientError> {
+        let Message::PlayerList(player_list) = self.request(
+            Message::RequestGuessingPlayers(category),
+            |message| matches!(message, Message::PlayerList(_)),
+            "player list",
+        )?
+        else {
+            return Err(ClientError::UnexpectedMessage("player list"));
+        };
+
+        Ok(player_list.opponent_ids)
+    }
+
+    /// Offers to guess a riddle of the given asking player.
+    pub fn challenge(&mut self, asking_player: PlayerId) -> Result<(), ClientError> {
+        let player_id = self.player_id.ok_or(ClientError::NotLoggedIn)?;
+        self.stream
+            .write(&Message::OpponentSelected(OpponentSelected {
+                guessing_player: player_id,
+                asking_player,
+            }))?;
+
+        Ok(())
+    }
+
+    pub fn request_house_riddle(&mut self) -> Result<(), ClientError> {
+        self.stream.write(&Message::RequestHouseRiddle)?;
+
+        Ok(())
+    }
+
+    /// Sends riddle to `riddle.guessing_player`, sender fields are filled in from the session.
+    pub fn send_riddle(&mut self, mut riddle: Riddle) -> Result<(), ClientError> {
+        let player_id = self.player_id.ok_or(ClientError::NotLoggedIn)?;
+        riddle.sender = player_id;
+        riddle.asking_player = player_id;
+        let guessing_player = riddle.guessing_player;
+        self.write_checked(&Message::Riddle(riddle))?;
+        self.opponent = Some(guessing_player);
+
+        Ok(())
+    }
+
+    pub fn guess(&mut self, guess: &str) -> Result<(), ClientError> {
+        let player_id = self.player_id.ok_or(ClientError::NotLoggedIn)?;
+        let asking_player = self.opponent.ok_or(ClientError::NoOpponent)?;
+        let riddle = Riddle {
+            sender: player_id,
+            asking_player,
+            guessing_player: player_id,
+            message: guess.into(),
+            ..Default::default()
+        };
+        self.write_checked(&Message::Riddle(riddle))?;
+
+        Ok(())
+    }
+
+    pub fn request_hint(&mut self) -> Result<(), ClientError> {
+        self.stream.write(&Message::RequestHint)?;
+
+        Ok(())
+    }
+
+    pub fn evaluate(&mut self, guessed: bool, hint: Option<String>) -> Result<(), ClientError> {
+        self.write_checked(&Message::Evaluation(Evaluation {
+            hint,
+            guessed,
+            ..Default::default()
+        }))?;
+        if guessed {
+            self.opponent = None;
+        }
+
+        Ok(())
+    }
+
+    pub fn chat(&mut self, scope: ChatScope, text: &str) -> Result<(), ClientError> {
+        let player_id = self.player_id.ok_or(ClientError::NotLoggedIn)?;
+        self.write_checked(&Message::ChatMessage(ChatMessage {
+            sender: player_id,
+            scope,
+            text: text.into(),
+        }))?;
+
+        Ok(())
+    }
+
+    /// Writes a message with user provided texts, refusing texts the binary format can not
+    /// carry.
+    fn write_checked(&mut self, message: &Message) -> Result<(), ClientError> {
+        if !fits_binary_format(message) {
+            return Err(ClientError::TooLong);
+        }
+        self.stream.write(message)?;
+
+        Ok(())
+    }
+
+    /// Returns next event, waiting until one arrives.
+    pub fn next_event(&mut self) -> Result<GameEvent, ClientError> {
+        let message = match self.pending.pop_front() {
+            Some(message) => message,
+            None => self.read_message()?,
+        };
+
+        Ok(self.handle_message(message))
+    }
+
+    /// Returns next event if one is available, without waiting on a non-blocking stream.
+    pub fn poll_event(&mut self) -> Result<Option<GameEvent>, ClientError> {
+        let message = match self.pending.pop_front() {
+            Some(message) => message,
+            None => match self.stream.read() {
+                Ok(message) => message,
+                Err(MessageError::EmptyRead) => return Ok(None),
+                Err(err) => return Err(err.into()),
+            },
+        };
+
+        Ok(Some(self.handle_message(message)))
+    }
+
+    fn handle_message(&mut self, message: Message) -> GameEvent {
+        let event = GameEvent::from_message(message, self.role == Some(Role::AskingPlayer));
+
+        match &event {
+            GameEvent::RiddleReceived(riddle) => {
+                self.opponent = Some(riddle.asking_player);
+            }
+            GameEvent::Evaluated(evaluation) if evaluation.guessed => self.opponent = None,
+            GameEvent::GameOver(_) => self.opponent = None,
+            GameEvent::PlayerNotAvailable(id) if self.opponent == Some(*id) => {
+                self.opponent = None;
+            }
+            _ => {}
+        }
+
+        event
+    }
+
+    pub fn events(&mut self) -> Events<'_, S> {
+        Events {
+            client: self,
+            finished: false,
+        }
+    }
+
+    pub fn shutdown(&mut self) -> Result<(), ClientError> {
+        self.stream.shutdown()?;
+
+        Ok(())
+    }
+
+    fn dispatch_unsolicited(&mut self, message: Message) {
+        let handled = self
+            .unsolicited_handlers
+            .iter_mut()
+            .any(|handler| handler(&message));
+        if !handled {
+            self.pending.push_back(message);
+        }
+    }
+
+    fn read_message(&mut self) -> Result<Message, ClientError> {
+        self.read_frame().map(|frame| frame.message)
+    }
+
+    fn read_frame(&mut self) -> Result<Frame, ClientError> {
+        loop {
+            match self.stream.read_frame() {
+                Err(MessageError::EmptyRead) => thread::sleep(time::Duration::from_millis(1)),
+                result => return Ok(result?),
+            }
+        }
+    }
+}
+
+pub struct Events<'a, S: Streamable> {
+    client: &'a mut GameClient<S>,
+    finished: bool,
+}
+
+impl<S: Streamable> Iterator for Events<'_, S> {
+    type Item = Result<GameEvent, ClientError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let event = self.client.next_event();
+        self.finished = event.is_err();
+        Some(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use messages::{memory_stream_pair, pack, unpack, ConnectionType, PlayerId, PlayerList};
+
+    #[derive(Default)]
+    struct ScriptedStream {
+        incoming: VecDeque<Message>,
+        outgoing: Vec<Vec<u8>>,
+    }
+
+    impl Streamable for ScriptedStream {
+        fn read(&mut self) -> Result<Message, MessageError> {
+            match self.incoming.pop_front() {
+                // Unknown stands in for a read that would block
+                Some(Message::Unknown) => Err(MessageError::EmptyRead),
+                Some(message) => Ok(message),
+                None => Err(MessageError::ConnectionClosed(ConnectionType::Tcp)),
+            }
+        }
+
+        fn write(&mut self, message: &Message) -> Result<(), MessageError> {
+            self.outgoing.push(pack(message)?);
+            Ok(())
+        }
+
+        fn shutdown(&mut self) -> Result<(), MessageError> {
+            Ok(())
+        }
+    }
+
+    fn logged_in_client(incoming: Vec<Message>) -> GameClient<ScriptedStream> {
+        let mut stream = ScriptedStream::default();
+        stream
+            .incoming
+            .push_back(Message::PlayerId(PlayerId::from_static("player0001")));
+        stream.incoming.extend(incoming);
+
+        let mut client = GameClient::new(stream);
+        client.login("secret").expect("Login should not fail");
+        client
+    }
+
+    #[test]
+    fn list_opponents_keeps_unsolicited_messages_as_events() {
+        let mut client = logged_in_client(vec![
+            Message::ChatMessage(ChatMessage {
+                sender: PlayerId::from_static("player0002"),
+                scope: ChatScope::Lobby,
+                text: "hello".into(),
+            }),
+            Message::PlayerList(PlayerList {
+                opponent_ids: vec![PlayerId::from_static("player0003")],
+            }),
+        ]);
+
+        let opponents = client.list_opponents(None).unwrap();
+        assert_eq!(opponents, vec![PlayerId::from_static("player0003")]);
+
+        match client.next_event().unwrap() {
+            GameEvent::Chat(chat_message) => assert_eq!(chat_message.text, "hello"),
+            event => panic!("event should be chat, got {:?}", event),
+        }
+    }
+
+    #[test]
+    fn request_waits_for_matching_request_id() {
+        let (stream, mut server) = memory_stream_pair();
+        let player_list = |request_id, opponent: &str| Frame {
+            request_id,
+            message: Message::PlayerList(PlayerList {
+                opponent_ids: vec![PlayerId::from_static(opponent)],
+            }),
+        };
+        server
+            .write_frame(&player_list(Some(7), "stale00001"))
+            .unwrap();
+        server
+            .write(&Message::OpponentSelected(OpponentSelected {
+                guessing_player: PlayerId::from_static("player0002"),
+                asking_player: PlayerId::from_static("player0001"),
+            }))
+            .unwrap();
+        server
+            .write(&Message::ChatMessage(ChatMessage {
+                sender: PlayerId::from_static("player0002"),
+                scope: ChatScope::Lobby,
+                text: "hello".into(),
+            }))
+            .unwrap();
+        server
+            .write_frame(&player_list(Some(1), "player0003"))
+            .unwrap();
+
+        let mut client = GameClient::new(stream);
+        let (chats_sent, chats) = std::sync::mpsc::channel();
+        client.on_unsolicited(move |message| match message {
+            Message::ChatMessage(chat_message) => {
+                chats_sent.send(chat_message.text.clone()).unwrap();
+                true
+            }
+            _ => false,
+        });
+
+        let opponents = client.list_opponents(None).unwrap();
+        assert_eq!(opponents, vec![PlayerId::from_static("player0003")]);
+        assert!(matches!(
+            server.read_frame(),
+            Ok(Frame {
+                request_id: Some(1),
+                message: Message::RequestGuessingPlayers(None),
+            })
+        ));
+        assert_eq!(chats.try_recv().unwrap(), "hello");
+
+        assert!(matches!(client.next_event().unwrap(), GameEvent::Unknown));
+        assert!(matches!(
+            client.next_event().unwrap(),
+            GameEvent::OpponentSelected(_)
+        ));
+    }
+
+    #[test]
+    fn guess_is_sent_to_riddle_sender() {
+        let mut client = logged_in_client(vec![Message::Riddle(Riddle {
+            sender: PlayerId::from_static("asking0001"),
+            asking_player: PlayerId::from_static("asking0001"),
+            guessing_player: PlayerId::from_static("player0001"),
+            ..Default::default()
+        })]);
+        client.register_role(Role::GuessingPlayer.into()).unwrap();
+
+        assert!(matches!(
+            client.guess("piano"),
+            Err(ClientError::NoOpponent)
+        ));
+        assert!(matches!(
+            client.next_event().unwrap(),
+            GameEvent::RiddleReceived(_)
+        ));
+        client.guess("piano").unwrap();
+
+        let last_message = client.stream.outgoing.last().unwrap();
+        match unpack(last_message).unwrap() {
+            Message::Riddle(guess) => {
+                assert_eq!(guess.asking_player, "asking0001");
+                assert_eq!(guess.guessing_player, "player0001");
+                assert_eq!(guess.message, "piano");
+            }
+            _ => panic!("message should be riddle"),
+        }
+    }
+
+    #[test]
+    fn too_long_riddle_is_refused() {
+        let mut client = logged_in_client(vec![]);
+        let sent_before = client.stream.outgoing.len();
+
+        let riddle = Riddle {
+            guessing_player: PlayerId::from_static("player0002"),
+            message: "a".repeat(256),
+            ..Default::default()
+        };
+        assert!(matches!(
+            client.send_riddle(riddle),
+            Err(ClientError::TooLong)
+        ));
+        assert_eq!(client.stream.outgoing.len(), sent_before);
+        assert!(client.opponent.is_none());
+    }
+
+    #[test]
+    fn evaluation_event_depends_on_role() {
+        let evaluation = || {
+            Message::Evaluation(Evaluation {
+                guessed: true,
+                ..Default::default()
+            })
+        };
+        let mut client = logged_in_client(vec![evaluation(), evaluation()]);
+
+        client.register_role(Role::AskingPlayer.into()).unwrap();
+        assert!(matches!(
+            client.next_event().unwrap(),
+            GameEvent::GuessEvaluated { guessed: true }
+        ));
+
+        client.register_role(Role::GuessingPlayer.into()).unwrap();
+        assert!(matches!(
+            client.next_event().unwrap(),
+            GameEvent::Evaluated(_)
+        ));
+    }
+
+    #[test]
+    fn poll_event_returns_none_without_pending_data() {
+        let mut client = logged_in_client(vec![Message::Unknown, Message::RequestHint]);
+
+        assert!(client.poll_event().unwrap().is_none());
+        assert!(matches!(
+            client.poll_event().unwrap(),
+            Some(GameEvent::HintRequested)
+        ));
+        assert!(client.poll_event().is_err());
+    }
+}
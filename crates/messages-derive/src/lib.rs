@@ -0,0 +1,266 @@
+//! `#[derive(Packable)]` for the message bodies of the `messages` crate.
+//!
+//! Structs with named fields pack their fields in declaration order, each through its
+//! `PackableField` implementation or the `pack`/`unpack` functions of the module given in
+//! `#[packable(with = path)]`. A derived struct is itself a `PackableField`, so it can be
+//! nested in other messages.
+//!
+//! Enums are packed as a type byte followed by the body of the variant, every variant
+//! needs a `#[message(tag = N)]`. Variants are unit variants or carry a single field,
+//! `#[message(tag = N, other)]` marks the unit variant unknown type bytes unpack to.
+//!
+//! The generated code names `PackableField`, `Packable` and `MessageError` through
+//! `crate::`, so the derive only works inside the messages crate.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse_macro_input, Attribute, Data, DataEnum, DataStruct, DeriveInput, Fields, Ident, LitInt,
+    Path, Type,
+};
+
+#[proc_macro_derive(Packable, attributes(packable, message))]
+pub fn derive_packable(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let expanded = match &input.data {
+        Data::Struct(data) => derive_struct(&input, data),
+        Data::Enum(data) => derive_enum(&input, data),
+        Data::Union(_) => Err(syn::Error::new_spanned(
+            &input.ident,
+            "Packable can not be derived for unions",
+        )),
+    };
+
+    expanded
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Packs and unpacks one value, through `PackableField` or a `with` module.
+struct FieldCodec<'a> {
+    ty: &'a Type,
+    with: Option<Path>,
+}
+
+impl FieldCodec<'_> {
+    fn pack(&self, value: TokenStream2) -> TokenStream2 {
+        let ty = self.ty;
+        match &self.with {
+            Some(with) => quote!(#with::pack(#value, packed)),
+            None => quote!(<#ty as crate::PackableField>::pack_field(#value, packed)),
+        }
+    }
+
+    fn unpack(&self) -> TokenStream2 {
+        let ty = self.ty;
+        match &self.with {
+            Some(with) => quote!(#with::unpack(msg_bytes, offset)),
+            None => quote!(<#ty as crate::PackableField>::unpack_field(msg_bytes, offset)),
+        }
+    }
+}
+
+fn parse_with(attrs: &[Attribute]) -> syn::Result<Option<Path>> {
+    let mut with = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("packable")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("with") {
+                with = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported packable attribute"))
+            }
+        })?;
+    }
+
+    Ok(with)
+}
+
+fn derive_struct(input: &DeriveInput, data: &DataStruct) -> syn::Result<TokenStream2> {
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "Packable can only be derived for structs with named fields",
+        ));
+    };
+
+    let mut pack_fields = Vec::new();
+    let mut unpack_fields = Vec::new();
+    for field in &fields.named {
+        let name = field.ident.as_ref().expect("named fields have a name");
+        let codec = FieldCodec {
+            ty: &field.ty,
+            with: parse_with(&field.attrs)?,
+        };
+
+        pack_fields.push(codec.pack(quote!(&self.#name)));
+        let unpack = codec.unpack();
+        unpack_fields.push(quote!(#name: #unpack?));
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics crate::PackableField for #name #ty_generics #where_clause {
+            fn pack_field(
+                &self,
+                packed: &mut ::std::vec::Vec<u8>,
+            ) -> ::std::result::Result<(), crate::MessageError> {
+                #(#pack_fields?;)*
+                ::std::result::Result::Ok(())
+            }
+
+            fn unpack_field(
+                msg_bytes: &[u8],
+                offset: &mut usize,
+            ) -> ::std::result::Result<Self, crate::MessageError> {
+                ::std::result::Result::Ok(#name {
+                    #(#unpack_fields,)*
+                })
+            }
+        }
+
+        impl #impl_generics crate::Packable for #name #ty_generics #where_clause {
+            fn pack(&self) -> ::std::result::Result<::std::vec::Vec<u8>, crate::MessageError> {
+                let mut packed = ::std::vec::Vec::new();
+                crate::PackableField::pack_field(self, &mut packed)?;
+                ::std::result::Result::Ok(packed)
+            }
+
+            fn unpack(
+                &mut self,
+                msg_bytes: &[u8],
+            ) -> ::std::result::Result<(), crate::MessageError> {
+                *self = <Self as crate::PackableField>::unpack_field(msg_bytes, &mut 0)?;
+                ::std::result::Result::Ok(())
+            }
+        }
+    })
+}
+
+struct MessageAttribute {
+    tag: u8,
+    other: bool,
+}
+
+fn parse_message(variant: &Ident, attrs: &[Attribute]) -> syn::Result<MessageAttribute> {
+    let mut tag = None;
+    let mut other = false;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("message")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("tag") {
+                tag = Some(meta.value()?.parse::<LitInt>()?.base10_parse::<u8>()?);
+                Ok(())
+            } else if meta.path.is_ident("other") {
+                other = true;
+                Ok(())
+            } else {
+                Err(meta.error("unsupported message attribute"))
+            }
+        })?;
+    }
+
+    match tag {
+        Some(tag) => Ok(MessageAttribute { tag, other }),
+        None => Err(syn::Error::new_spanned(
+            variant,
+            "every variant needs a #[message(tag = N)] attribute",
+        )),
+    }
+}
+
+fn derive_enum(input: &DeriveInput, data: &DataEnum) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let mut tags = Vec::new();
+    let mut pack_arms = Vec::new();
+    let mut unpack_arms = Vec::new();
+    let mut other_arm = None;
+
+    for variant in &data.variants {
+        let variant_name = &variant.ident;
+        let MessageAttribute { tag, other } = parse_message(variant_name, &variant.attrs)?;
+        if tags.contains(&tag) {
+            return Err(syn::Error::new_spanned(variant, "duplicate message tag"));
+        }
+        tags.push(tag);
+
+        match &variant.fields {
+            Fields::Unit => {
+                pack_arms.push(quote!(#name::#variant_name => packed.push(#tag)));
+                if other {
+                    other_arm = Some(quote!(_ => ::std::result::Result::Ok(#name::#variant_name)));
+                } else {
+                    unpack_arms
+                        .push(quote!(#tag => ::std::result::Result::Ok(#name::#variant_name)));
+                }
+            }
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 && !other => {
+                let field = &fields.unnamed[0];
+                let codec = FieldCodec {
+                    ty: &field.ty,
+                    with: parse_with(&variant.attrs)?,
+                };
+
+                let pack = codec.pack(quote!(field));
+                pack_arms.push(quote! {
+                    #name::#variant_name(field) => {
+                        packed.push(#tag);
+                        #pack?;
+                    }
+                });
+                let unpack = codec.unpack();
+                unpack_arms.push(quote!(#tag => #unpack.map(#name::#variant_name)));
+            }
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    variant,
+                    "message variants carry a single field, the other variant none",
+                ))
+            }
+        }
+    }
+
+    let other_arm = other_arm.unwrap_or_else(|| {
+        quote! {
+            _ => {
+                return ::std::result::Result::Err(crate::MessageError::Decode {
+                    message_type: ::std::option::Option::Some(tag),
+                    offset: 0,
+                    reason: "Unknown message type",
+                })
+            }
+        }
+    });
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics crate::Packable for #name #ty_generics #where_clause {
+            fn pack(&self) -> ::std::result::Result<::std::vec::Vec<u8>, crate::MessageError> {
+                let mut message = ::std::vec::Vec::new();
+                let packed = &mut message;
+                match self {
+                    #(#pack_arms,)*
+                }
+                ::std::result::Result::Ok(message)
+            }
+
+            fn unpack(
+                &mut self,
+                msg_bytes: &[u8],
+            ) -> ::std::result::Result<(), crate::MessageError> {
+                let ::std::option::Option::Some((&tag, msg_bytes)) = msg_bytes.split_first() else {
+                    return ::std::result::Result::Err(crate::MessageError::EmptyFrame);
+                };
+
+                let offset = &mut 0;
+                let message = match tag {
+                    #(#unpack_arms,)*
+                    #other_arm,
+                };
+                *self = message.map_err(|error| error.within(1, ::std::option::Option::Some(tag)))?;
+                ::std::result::Result::Ok(())
+            }
+        }
+    })
+}
@@ -0,0 +1,12 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use messages::{pack_frame, unpack_frame};
+
+// Any byte sequence has to be rejected or unpacked without panicking,
+// and whatever unpacks has to pack again.
+fuzz_target!(|data: &[u8]| {
+    if let Ok(frame) = unpack_frame(data) {
+        pack_frame(&frame).expect("Unpacked frames should pack again");
+    }
+});
@@ -0,0 +1,262 @@
+//! Field encodings of the message bodies, see `PackableField`.
+//!
+//! Integers are big-endian, strings and lists carry a one byte length. The modules below
+//! are the encodings picked with `#[packable(with = ...)]`.
+
+use crate::helpers::{merge_u8, split_u16};
+use crate::traits::PackableField;
+use crate::MessageError;
+
+fn take<'a>(
+    msg_bytes: &'a [u8],
+    offset: &mut usize,
+    length: usize,
+    missing: &'static str,
+) -> Result<&'a [u8], MessageError> {
+    let end = *offset + length;
+    let bytes = msg_bytes
+        .get(*offset..end)
+        .ok_or(MessageError::decode(*offset, missing))?;
+    *offset = end;
+    Ok(bytes)
+}
+
+/// Decodes `bytes` found at `start`, pointing at the first invalid byte otherwise.
+fn to_string(bytes: &[u8], start: usize) -> Result<String, MessageError> {
+    String::from_utf8(bytes.to_vec()).map_err(|error| {
+        MessageError::decode(
+            start + error.utf8_error().valid_up_to(),
+            "Invalid UTF-8 sequence",
+        )
+    })
+}
+
+impl PackableField for u8 {
+    fn pack_field(&self, packed: &mut Vec<u8>) -> Result<(), MessageError> {
+        packed.push(*self);
+        Ok(())
+    }
+
+    fn unpack_field(msg_bytes: &[u8], offset: &mut usize) -> Result<Self, MessageError> {
+        let bytes = take(msg_bytes, offset, 1, "Invalid message format: byte missing")?;
+        Ok(bytes[0])
+    }
+}
+
+impl PackableField for u16 {
+    fn pack_field(&self, packed: &mut Vec<u8>) -> Result<(), MessageError> {
+        let (high, low) = split_u16(*self);
+        packed.push(high);
+        packed.push(low);
+        Ok(())
+    }
+
+    fn unpack_field(msg_bytes: &[u8], offset: &mut usize) -> Result<Self, MessageError> {
+        let bytes = take(msg_bytes, offset, 2, "Invalid message format: u16 missing")?;
+        Ok(merge_u8(bytes[0], bytes[1]))
+    }
+}
+
+impl PackableField for u32 {
+    fn pack_field(&self, packed: &mut Vec<u8>) -> Result<(), MessageError> {
+        packed.extend_from_slice(&self.to_be_bytes());
+        Ok(())
+    }
+
+    fn unpack_field(msg_bytes: &[u8], offset: &mut usize) -> Result<Self, MessageError> {
+        let bytes = take(msg_bytes, offset, 4, "Invalid message format: u32 missing")?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+}
+
+impl PackableField for bool {
+    fn pack_field(&self, packed: &mut Vec<u8>) -> Result<(), MessageError> {
+        packed.push(*self as u8);
+        Ok(())
+    }
+
+    fn unpack_field(msg_bytes: &[u8], offset: &mut usize) -> Result<Self, MessageError> {
+        u8::unpack_field(msg_bytes, offset).map(|byte| byte != 0)
+    }
+}
+
+fn pack_string(value: &str, packed: &mut Vec<u8>) -> Result<(), MessageError> {
+    let length = u8::try_from(value.len()).map_err(|_| MessageError::TextTooLong(value.len()))?;
+    packed.push(length);
+    packed.extend_from_slice(value.as_bytes());
+    Ok(())
+}
+
+fn pack_list<T: PackableField>(items: &[T], packed: &mut Vec<u8>) -> Result<(), MessageError> {
+    let count = u8::try_from(items.len()).map_err(|_| MessageError::ListTooLong(items.len()))?;
+    packed.push(count);
+    items.iter().try_for_each(|item| item.pack_field(packed))
+}
+
+impl PackableField for String {
+    fn pack_field(&self, packed: &mut Vec<u8>) -> Result<(), MessageError> {
+        pack_string(self, packed)
+    }
+
+    fn unpack_field(msg_bytes: &[u8], offset: &mut usize) -> Result<Self, MessageError> {
+        let length = take(
+            msg_bytes,
+            offset,
+            1,
+            "Invalid message format: string length missing",
+        )?[0];
+        let start = *offset;
+        let bytes = take(
+            msg_bytes,
+            offset,
+            length as usize,
+            "Invalid message format: string truncated",
+        )?;
+        to_string(bytes, start)
+    }
+}
+
+/// Packed like a string, the empty string stands for `None`.
+impl PackableField for Option<String> {
+    fn pack_field(&self, packed: &mut Vec<u8>) -> Result<(), MessageError> {
+        pack_string(self.as_deref().unwrap_or_default(), packed)
+    }
+
+    fn unpack_field(msg_bytes: &[u8], offset: &mut usize) -> Result<Self, MessageError> {
+        String::unpack_field(msg_bytes, offset).map(|value| Some(value).filter(|v| !v.is_empty()))
+    }
+}
+
+/// Packed as the item count followed by the items, lists have at most 255 items.
+impl<T: PackableField> PackableField for Vec<T> {
+    fn pack_field(&self, packed: &mut Vec<u8>) -> Result<(), MessageError> {
+        pack_list(self, packed)
+    }
+
+    fn unpack_field(msg_bytes: &[u8], offset: &mut usize) -> Result<Self, MessageError> {
+        let count = u8::unpack_field(msg_bytes, offset)?;
+        (0..count)
+            .map(|_| T::unpack_field(msg_bytes, offset))
+            .collect()
+    }
+}
+
+/// String taking the rest of the body, without a length.
+pub mod rest {
+    use super::*;
+
+    pub fn pack(value: &str, packed: &mut Vec<u8>) -> Result<(), MessageError> {
+        packed.extend_from_slice(value.as_bytes());
+        Ok(())
+    }
+
+    pub fn unpack(msg_bytes: &[u8], offset: &mut usize) -> Result<String, MessageError> {
+        let start = (*offset).min(msg_bytes.len());
+        *offset = msg_bytes.len();
+        to_string(&msg_bytes[start..], start)
+    }
+}
+
+/// Like `rest`, an empty rest stands for `None`.
+pub mod optional_rest {
+    use super::*;
+
+    pub fn pack(value: &Option<String>, packed: &mut Vec<u8>) -> Result<(), MessageError> {
+        rest::pack(value.as_deref().unwrap_or_default(), packed)
+    }
+
+    pub fn unpack(msg_bytes: &[u8], offset: &mut usize) -> Result<Option<String>, MessageError> {
+        rest::unpack(msg_bytes, offset).map(|value| Some(value).filter(|v| !v.is_empty()))
+    }
+}
+
+/// List left out entirely when empty, so it can follow the fields of older messages.
+pub mod trailing {
+    use super::*;
+
+    pub fn pack<T: PackableField>(items: &[T], packed: &mut Vec<u8>) -> Result<(), MessageError> {
+        if items.is_empty() {
+            return Ok(());
+        }
+        pack_list(items, packed)
+    }
+
+    pub fn unpack<T: PackableField>(
+        msg_bytes: &[u8],
+        offset: &mut usize,
+    ) -> Result<Vec<T>, MessageError> {
+        if *offset >= msg_bytes.len() {
+            return Ok(Vec::new());
+        }
+        Vec::unpack_field(msg_bytes, offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip<T: PackableField>(value: &T) -> (T, usize) {
+        let mut packed = Vec::new();
+        value.pack_field(&mut packed).unwrap();
+        let mut offset = 0;
+        let unpacked = T::unpack_field(&packed, &mut offset).expect("Unpacking should not fail");
+        assert_eq!(offset, packed.len());
+        (unpacked, offset)
+    }
+
+    #[test]
+    fn fields_round_trip() {
+        assert_eq!(round_trip(&0xabcd_u16), (0xabcd, 2));
+        assert_eq!(round_trip(&0x0102_0304_u32), (0x0102_0304, 4));
+        assert_eq!(round_trip(&true), (true, 1));
+        assert_eq!(round_trip(&String::from("hint")), (String::from("hint"), 5));
+        assert_eq!(round_trip(&None::<String>), (None, 1));
+        assert_eq!(
+            round_trip(&vec![String::from("a"), String::from("bc")]),
+            (vec![String::from("a"), String::from("bc")], 6)
+        );
+    }
+
+    #[test]
+    fn decode_errors_point_at_the_failing_byte() {
+        let mut offset = 0;
+        assert!(matches!(
+            String::unpack_field(&[3, b'a'], &mut offset),
+            Err(MessageError::Decode { offset: 1, .. })
+        ));
+
+        let mut offset = 0;
+        assert!(matches!(
+            String::unpack_field(&[3, b'a', 0xff, b'c'], &mut offset),
+            Err(MessageError::Decode { offset: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn oversized_fields_are_not_packed() {
+        let mut packed = Vec::new();
+        assert!(matches!(
+            "a".repeat(256).pack_field(&mut packed),
+            Err(MessageError::TextTooLong(256))
+        ));
+
+        let items = vec![true; 256];
+        assert!(matches!(
+            items.pack_field(&mut packed),
+            Err(MessageError::ListTooLong(256))
+        ));
+        assert!(items[..255].to_vec().pack_field(&mut packed).is_ok());
+    }
+
+    #[test]
+    fn empty_trailing_lists_are_left_out() {
+        let mut packed = Vec::new();
+        trailing::pack::<String>(&Vec::new(), &mut packed).unwrap();
+        assert!(packed.is_empty());
+
+        let mut offset = 0;
+        let unpacked: Vec<String> = trailing::unpack(&packed, &mut offset).unwrap();
+        assert!(unpacked.is_empty());
+    }
+}
@@ -1,2 +1,9 @@
+pub mod frame_reader;
+pub mod framed;
+pub mod json_lines;
+pub mod memory;
+pub mod recording;
 pub mod tcp;
+pub mod tls;
 pub mod unix_socket;
+pub mod websocket;
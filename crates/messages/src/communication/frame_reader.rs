@@ -0,0 +1,259 @@
+use crate::constants::HEADERS_LEN;
+use crate::ConnectionType;
+use crate::Frame;
+use crate::MessageError;
+use crate::{parse_header, unpack_frame_body};
+use std::io::{self, Read};
+
+const READ_CHUNK_LEN: usize = 4096;
+
+/// Accumulates bytes across reads and yields messages once their whole frame arrived.
+///
+/// A read that stops in the middle of a frame keeps the received bytes, so frames
+/// arriving in pieces on non-blocking sockets are not lost.
+#[derive(Default)]
+pub struct FrameReader {
+    buffer: Vec<u8>,
+}
+
+impl FrameReader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds received bytes to the buffer.
+    pub fn extend(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Number of buffered bytes not yet returned as a message.
+    pub fn buffered_len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Takes the next complete frame out of the buffer, `None` until one is complete.
+    ///
+    /// An invalid header drops the buffer, the stream can not be trusted after it.
+    pub fn next_frame(&mut self) -> Option<Result<Frame, MessageError>> {
+        let header = self.buffer.first_chunk::<HEADERS_LEN>()?;
+        let header = match parse_header(header) {
+            Ok(header) => header,
+            Err(error) => {
+                self.buffer.clear();
+                return Some(Err(error));
+            }
+        };
+
+        let frame_len = HEADERS_LEN + header.length;
+        if self.buffer.len() < frame_len {
+            return None;
+        }
+
+        let frame = unpack_frame_body(&header, &self.buffer[HEADERS_LEN..frame_len]);
+        self.buffer.drain(..frame_len);
+        Some(frame)
+    }
+
+    /// Returns the next buffered frame, reading from `stream` until one is complete.
+    ///
+    /// `EmptyRead` is returned when the stream would block before that, `ConnectionClosed`
+    /// when it ends between two frames.
+    pub fn read_from(
+        &mut self,
+        stream: &mut impl Read,
+        connection_type: ConnectionType,
+    ) -> Result<Frame, MessageError> {
+        let mut chunk = [0_u8; READ_CHUNK_LEN];
+        loop {
+            if let Some(frame) = self.next_frame() {
+                return frame;
+            }
+
+            match stream.read(&mut chunk) {
+                Ok(0) if self.buffer.is_empty() => {
+                    return Err(MessageError::ConnectionClosed(connection_type))
+                }
+                Ok(0) => {
+                    return Err(MessageError::InvalidRead(
+                        connection_type,
+                        io::ErrorKind::UnexpectedEof.into(),
+                    ))
+                }
+                Ok(read) => self.extend(&chunk[..read]),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    return Err(MessageError::EmptyRead)
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(MessageError::InvalidRead(connection_type, e)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{pack, pack_frame, ChatMessage, ChatScope, Message, PlayerId};
+    use std::collections::VecDeque;
+
+    /// Delivers one byte per read, blocking between bytes.
+    struct TricklingStream {
+        bytes: VecDeque<u8>,
+        blocked: bool,
+    }
+
+    impl TricklingStream {
+        fn new(bytes: Vec<u8>) -> Self {
+            TricklingStream {
+                bytes: bytes.into(),
+                blocked: false,
+            }
+        }
+    }
+
+    impl Read for TricklingStream {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.blocked = !self.blocked;
+            if self.blocked {
+                return Err(io::ErrorKind::WouldBlock.into());
+            }
+            match self.bytes.pop_front() {
+                Some(byte) => {
+                    buf[0] = byte;
+                    Ok(1)
+                }
+                None => Ok(0),
+            }
+        }
+    }
+
+    fn chat_message(text: &str) -> Message {
+        Message::ChatMessage(ChatMessage {
+            sender: PlayerId::from_static("player0001"),
+            scope: ChatScope::Lobby,
+            text: text.into(),
+        })
+    }
+
+    #[test]
+    fn byte_by_byte_delivery_yields_whole_messages() {
+        let first = pack(&chat_message("hello")).unwrap();
+        let second = pack(&Message::PlayerId(PlayerId::from_static("player0002"))).unwrap();
+        let mut stream = TricklingStream::new([first.clone(), second.clone()].concat());
+        let mut reader = FrameReader::new();
+
+        let mut empty_reads = 0;
+        let mut messages = Vec::new();
+        loop {
+            match reader.read_from(&mut stream, ConnectionType::InMemory) {
+                Ok(frame) => messages.push(frame.message),
+                Err(MessageError::EmptyRead) => empty_reads += 1,
+                Err(MessageError::ConnectionClosed(ConnectionType::InMemory)) => break,
+                Err(error) => panic!("unexpected error {:?}", error),
+            }
+        }
+
+        assert_eq!(empty_reads, first.len() + second.len() + 1);
+        assert_eq!(messages.len(), 2);
+        assert_eq!(pack(&messages[0]).unwrap(), first);
+        assert_eq!(pack(&messages[1]).unwrap(), second);
+        assert_eq!(reader.buffered_len(), 0);
+    }
+
+    #[test]
+    fn partial_frames_wait_for_the_rest() {
+        let frame = pack(&chat_message("split")).unwrap();
+        let mut reader = FrameReader::new();
+
+        for split in [2, HEADERS_LEN, frame.len() - 1] {
+            reader.extend(&frame[..split]);
+            assert!(reader.next_frame().is_none());
+            assert_eq!(reader.buffered_len(), split);
+
+            reader.extend(&frame[split..]);
+            let read = reader.next_frame().unwrap().unwrap();
+            assert_eq!(pack(&read.message).unwrap(), frame);
+        }
+    }
+
+    #[test]
+    fn several_frames_in_one_read_are_all_returned() {
+        let mut reader = FrameReader::new();
+        reader.extend(
+            &[
+                pack(&Message::GameStart).unwrap(),
+                pack(&Message::RequestHint).unwrap(),
+            ]
+            .concat(),
+        );
+
+        assert!(matches!(
+            reader.next_frame(),
+            Some(Ok(Frame {
+                message: Message::GameStart,
+                ..
+            }))
+        ));
+        assert!(matches!(
+            reader.next_frame(),
+            Some(Ok(Frame {
+                message: Message::RequestHint,
+                ..
+            }))
+        ));
+        assert!(reader.next_frame().is_none());
+    }
+
+    #[test]
+    fn request_ids_are_kept() {
+        let mut reader = FrameReader::new();
+        reader.extend(
+            &pack_frame(&Frame {
+                request_id: Some(7),
+                message: Message::RequestGuessingPlayers(None),
+            })
+            .unwrap(),
+        );
+        reader.extend(&pack(&Message::GameStart).unwrap());
+
+        assert!(matches!(
+            reader.next_frame(),
+            Some(Ok(Frame {
+                request_id: Some(7),
+                message: Message::RequestGuessingPlayers(None),
+            }))
+        ));
+        assert!(matches!(
+            reader.next_frame(),
+            Some(Ok(Frame {
+                request_id: None,
+                message: Message::GameStart,
+            }))
+        ));
+    }
+
+    #[test]
+    fn end_of_stream_inside_a_frame_is_not_a_clean_close() {
+        let frame = pack(&chat_message("cut")).unwrap();
+        let mut stream = io::Cursor::new(frame[..frame.len() - 1].to_vec());
+        let mut reader = FrameReader::new();
+
+        let error = reader
+            .read_from(&mut stream, ConnectionType::InMemory)
+            .unwrap_err();
+        assert_eq!(error.io_kind(), Some(io::ErrorKind::UnexpectedEof));
+        assert!(std::error::Error::source(&error).is_some());
+    }
+
+    #[test]
+    fn invalid_header_drops_the_buffer() {
+        let mut reader = FrameReader::new();
+        reader.extend(b"GET / HTTP/1.1");
+
+        assert!(matches!(
+            reader.next_frame(),
+            Some(Err(MessageError::InvalidPrefix))
+        ));
+        assert_eq!(reader.buffered_len(), 0);
+    }
+}
@@ -0,0 +1,151 @@
+use super::frame_reader::FrameReader;
+use crate::traits::Streamable;
+use crate::ConnectionType;
+use crate::Message;
+use crate::MessageError;
+use crate::{pack, pack_frame, Frame};
+use std::io::{self, Read, Write};
+
+type ShutdownHook<T> = Box<dyn FnMut(&mut T) -> io::Result<()> + Send>;
+
+/// Streamable over any byte stream, handling the `GME` framing of messages.
+///
+/// Partially received frames are buffered between reads, see `FrameReader`.
+/// `connection_type` is only used in errors. Without a shutdown hook, `shutdown` just
+/// flushes the stream.
+pub struct FramedStream<T: Read + Write + Send> {
+    stream: T,
+    frame_reader: FrameReader,
+    connection_type: ConnectionType,
+    shutdown_hook: Option<ShutdownHook<T>>,
+}
+
+impl<T: Read + Write + Send> FramedStream<T> {
+    pub fn new(stream: T, connection_type: ConnectionType) -> Self {
+        FramedStream {
+            stream,
+            frame_reader: FrameReader::new(),
+            connection_type,
+            shutdown_hook: None,
+        }
+    }
+
+    pub fn with_shutdown(
+        mut self,
+        shutdown_hook: impl FnMut(&mut T) -> io::Result<()> + Send + 'static,
+    ) -> Self {
+        self.shutdown_hook = Some(Box::new(shutdown_hook));
+        self
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.stream
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.stream
+    }
+
+    /// Bytes buffered by a read but not yet returned as a message are dropped.
+    pub fn into_inner(self) -> T {
+        self.stream
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), MessageError> {
+        self.stream
+            .write_all(bytes)
+            .and_then(|_| self.stream.flush())
+            .map_err(|e| MessageError::InvalidWrite(self.connection_type, e))
+    }
+}
+
+impl<T: Read + Write + Send> Streamable for FramedStream<T> {
+    fn read(&mut self) -> Result<Message, MessageError> {
+        self.read_frame().map(|frame| frame.message)
+    }
+
+    fn write(&mut self, message: &Message) -> Result<(), MessageError> {
+        self.write_bytes(&pack(message)?)
+    }
+
+    fn read_frame(&mut self) -> Result<Frame, MessageError> {
+        self.frame_reader
+            .read_from(&mut self.stream, self.connection_type)
+    }
+
+    fn write_frame(&mut self, frame: &Frame) -> Result<(), MessageError> {
+        self.write_bytes(&pack_frame(frame)?)
+    }
+
+    fn shutdown(&mut self) -> Result<(), MessageError> {
+        let result = match self.shutdown_hook.as_mut() {
+            Some(shutdown_hook) => shutdown_hook(&mut self.stream),
+            None => self.stream.flush(),
+        };
+
+        result.map_err(|e| MessageError::InvalidWrite(self.connection_type, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PlayerId;
+    use std::io::Cursor;
+
+    #[test]
+    fn written_frames_can_be_read_back() {
+        let mut stream = FramedStream::new(Cursor::new(Vec::new()), ConnectionType::InMemory);
+        stream.write(&Message::RequestHint).unwrap();
+        stream
+            .write(&Message::PlayerId(PlayerId::from_static("player0001")))
+            .unwrap();
+
+        stream.get_mut().set_position(0);
+        assert!(matches!(stream.read(), Ok(Message::RequestHint)));
+        match stream.read() {
+            Ok(Message::PlayerId(player_id)) => assert_eq!(player_id, "player0001"),
+            message => panic!("message should be player id, got {:?}", message),
+        }
+        assert!(matches!(
+            stream.read(),
+            Err(MessageError::ConnectionClosed(ConnectionType::InMemory))
+        ));
+    }
+
+    #[test]
+    fn invalid_headers_are_rejected() {
+        let mut stream = FramedStream::new(
+            Cursor::new(b"HTTP/1.1 200 OK".to_vec()),
+            ConnectionType::InMemory,
+        );
+        assert!(matches!(stream.read(), Err(MessageError::InvalidPrefix)));
+
+        let mut stream = FramedStream::new(
+            Cursor::new(b"GME\xff\xff".to_vec()),
+            ConnectionType::InMemory,
+        );
+        assert!(matches!(
+            stream.read(),
+            Err(MessageError::FrameTooLong(0xffff))
+        ));
+    }
+
+    #[test]
+    fn shutdown_hook_is_called() {
+        let mut stream = FramedStream::new(Cursor::new(Vec::new()), ConnectionType::InMemory)
+            .with_shutdown(|cursor| {
+                cursor.get_mut().clear();
+                Err(io::Error::other("already closed"))
+            });
+        stream.write(&Message::GameStart).unwrap();
+
+        match stream.shutdown() {
+            Err(MessageError::InvalidWrite(ConnectionType::InMemory, error)) => {
+                assert_eq!(error.to_string(), "already closed")
+            }
+            result => panic!("shutdown should fail, got {:?}", result),
+        }
+        assert!(stream.into_inner().into_inner().is_empty());
+    }
+}
@@ -0,0 +1,240 @@
+use crate::traits::Streamable;
+use crate::{
+    frame_from_json, frame_to_json, to_json, ConnectionType, Frame, Message, MessageError,
+    MAX_JSON_MESSAGE_LENGTH,
+};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::mem;
+use std::net::{Shutdown, TcpStream};
+
+type ShutdownHook<T> = Box<dyn FnMut(&mut T) -> io::Result<()> + Send>;
+
+/// Streamable exchanging one JSON message per line, see `to_json`.
+///
+/// Partially received lines are kept between reads, so it is safe to use on
+/// non-blocking streams. Blank lines are skipped.
+pub struct JsonLinesStream<T: Read + Write + Send> {
+    reader: BufReader<T>,
+    line: Vec<u8>,
+    connection_type: ConnectionType,
+    shutdown_hook: Option<ShutdownHook<T>>,
+}
+
+pub type TcpJsonStream = JsonLinesStream<TcpStream>;
+
+impl From<TcpStream> for TcpJsonStream {
+    fn from(stream: TcpStream) -> Self {
+        JsonLinesStream::new(stream, ConnectionType::Tcp)
+            .with_shutdown(|stream| stream.shutdown(Shutdown::Both))
+    }
+}
+
+impl<T: Read + Write + Send> JsonLinesStream<T> {
+    pub fn new(stream: T, connection_type: ConnectionType) -> Self {
+        JsonLinesStream {
+            reader: BufReader::new(stream),
+            line: Vec::new(),
+            connection_type,
+            shutdown_hook: None,
+        }
+    }
+
+    pub fn with_shutdown(
+        mut self,
+        shutdown_hook: impl FnMut(&mut T) -> io::Result<()> + Send + 'static,
+    ) -> Self {
+        self.shutdown_hook = Some(Box::new(shutdown_hook));
+        self
+    }
+
+    pub fn get_ref(&self) -> &T {
+        self.reader.get_ref()
+    }
+
+    fn parse_line(&mut self) -> Option<Result<Frame, MessageError>> {
+        let line = mem::take(&mut self.line);
+        let line = match String::from_utf8(line) {
+            Ok(line) => line,
+            Err(error) => {
+                return Some(Err(MessageError::decode(
+                    error.utf8_error().valid_up_to(),
+                    "Invalid UTF-8 in JSON line",
+                )))
+            }
+        };
+
+        let line = line.trim();
+        if line.is_empty() {
+            return None;
+        }
+        Some(frame_from_json(line))
+    }
+
+    fn write_line(&mut self, mut line: String) -> Result<(), MessageError> {
+        line.push('\n');
+
+        let stream = self.reader.get_mut();
+        stream
+            .write_all(line.as_bytes())
+            .and_then(|_| stream.flush())
+            .map_err(|e| MessageError::InvalidWrite(self.connection_type, e))
+    }
+}
+
+impl<T: Read + Write + Send> Streamable for JsonLinesStream<T> {
+    fn read(&mut self) -> Result<Message, MessageError> {
+        self.read_frame().map(|frame| frame.message)
+    }
+
+    fn write(&mut self, message: &Message) -> Result<(), MessageError> {
+        self.write_line(to_json(message))
+    }
+
+    fn read_frame(&mut self) -> Result<Frame, MessageError> {
+        loop {
+            // one byte over the limit tells a too long line from a complete one
+            let limit = (MAX_JSON_MESSAGE_LENGTH + 2).saturating_sub(self.line.len());
+            let result = self
+                .reader
+                .by_ref()
+                .take(limit as u64)
+                .read_until(b'\n', &mut self.line);
+
+            match result {
+                Ok(_) if self.line.ends_with(b"\n") => {
+                    if let Some(message) = self.parse_line() {
+                        return message;
+                    }
+                }
+                Ok(_) if self.line.len() > MAX_JSON_MESSAGE_LENGTH => {
+                    let length = self.line.len();
+                    self.line.clear();
+                    return Err(MessageError::FrameTooLong(length));
+                }
+                Ok(_) if self.line.is_empty() => {
+                    return Err(MessageError::ConnectionClosed(self.connection_type))
+                }
+                Ok(_) => {
+                    return Err(MessageError::InvalidRead(
+                        self.connection_type,
+                        io::ErrorKind::UnexpectedEof.into(),
+                    ))
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    return Err(MessageError::EmptyRead)
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(MessageError::InvalidRead(self.connection_type, e)),
+            }
+        }
+    }
+
+    fn write_frame(&mut self, frame: &Frame) -> Result<(), MessageError> {
+        self.write_line(frame_to_json(frame))
+    }
+
+    fn shutdown(&mut self) -> Result<(), MessageError> {
+        let stream = self.reader.get_mut();
+        let result = match self.shutdown_hook.as_mut() {
+            Some(shutdown_hook) => shutdown_hook(stream),
+            None => stream.flush(),
+        };
+
+        result.map_err(|e| MessageError::InvalidWrite(self.connection_type, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PlayerId;
+    use std::collections::VecDeque;
+
+    /// Delivers the queued chunks one read at a time, blocking in between.
+    #[derive(Default)]
+    struct ChunkedStream {
+        chunks: VecDeque<Option<Vec<u8>>>,
+        written: Vec<u8>,
+    }
+
+    impl Read for ChunkedStream {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match self.chunks.pop_front() {
+                Some(Some(mut chunk)) => {
+                    let length = chunk.len().min(buf.len());
+                    buf[..length].copy_from_slice(&chunk[..length]);
+                    if length < chunk.len() {
+                        self.chunks.push_front(Some(chunk.split_off(length)));
+                    }
+                    Ok(length)
+                }
+                Some(None) => Err(io::ErrorKind::WouldBlock.into()),
+                None => Ok(0),
+            }
+        }
+    }
+
+    impl Write for ChunkedStream {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.written.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn chunked(chunks: Vec<Option<&str>>) -> JsonLinesStream<ChunkedStream> {
+        let stream = ChunkedStream {
+            chunks: chunks
+                .into_iter()
+                .map(|chunk| chunk.map(|chunk| chunk.as_bytes().to_vec()))
+                .collect(),
+            ..Default::default()
+        };
+        JsonLinesStream::new(stream, ConnectionType::InMemory)
+    }
+
+    #[test]
+    fn partial_lines_are_kept_between_reads() {
+        let mut stream = chunked(vec![
+            Some(r#"{"type":"player_id","#),
+            None,
+            Some("\"player_id\":\"player0001\"}\n\n"),
+            Some("{\"type\":\"request_hint\"}\n"),
+        ]);
+
+        assert!(matches!(stream.read(), Err(MessageError::EmptyRead)));
+        match stream.read() {
+            Ok(Message::PlayerId(player_id)) => assert_eq!(player_id, "player0001"),
+            message => panic!("message should be player id, got {:?}", message),
+        }
+        assert!(matches!(stream.read(), Ok(Message::RequestHint)));
+        assert!(matches!(
+            stream.read(),
+            Err(MessageError::ConnectionClosed(ConnectionType::InMemory))
+        ));
+    }
+
+    #[test]
+    fn written_messages_are_json_lines() {
+        let mut stream = chunked(vec![]);
+        stream
+            .write(&Message::PlayerId(PlayerId::from_static("player0001")))
+            .unwrap();
+        stream.write(&Message::GameStart).unwrap();
+
+        assert_eq!(
+            String::from_utf8(stream.get_ref().written.clone()).unwrap(),
+            "{\"type\":\"player_id\",\"player_id\":\"player0001\"}\n{\"type\":\"game_start\"}\n"
+        );
+    }
+
+    #[test]
+    fn too_long_line_is_rejected() {
+        let long_line = "a".repeat(MAX_JSON_MESSAGE_LENGTH + 10);
+        let mut stream = chunked(vec![Some(&long_line)]);
+
+        assert!(matches!(stream.read(), Err(MessageError::FrameTooLong(_))));
+    }
+}
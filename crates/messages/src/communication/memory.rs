@@ -0,0 +1,115 @@
+use crate::traits::Streamable;
+use crate::ConnectionType;
+use crate::Message;
+use crate::MessageError;
+use crate::{pack, pack_frame, unpack_frame, Frame};
+use std::io;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+
+/// One end of an in-memory duplex connection, created with `memory_stream_pair`.
+///
+/// Frames are packed on write and unpacked on read like on a socket, and reads never
+/// block, returning `EmptyRead` when nothing has arrived yet.
+pub struct MemoryMessageStream {
+    sender: Option<Sender<Vec<u8>>>,
+    receiver: Receiver<Vec<u8>>,
+}
+
+pub fn memory_stream_pair() -> (MemoryMessageStream, MemoryMessageStream) {
+    let (first_sender, second_receiver) = mpsc::channel();
+    let (second_sender, first_receiver) = mpsc::channel();
+
+    (
+        MemoryMessageStream {
+            sender: Some(first_sender),
+            receiver: first_receiver,
+        },
+        MemoryMessageStream {
+            sender: Some(second_sender),
+            receiver: second_receiver,
+        },
+    )
+}
+
+impl MemoryMessageStream {
+    fn send(&self, frame: Vec<u8>) -> Result<(), MessageError> {
+        self.sender
+            .as_ref()
+            .ok_or_else(closed_write)?
+            .send(frame)
+            .map_err(|_| closed_write())
+    }
+}
+
+fn closed_write() -> MessageError {
+    MessageError::InvalidWrite(ConnectionType::InMemory, io::ErrorKind::BrokenPipe.into())
+}
+
+impl Streamable for MemoryMessageStream {
+    fn read(&mut self) -> Result<Message, MessageError> {
+        self.read_frame().map(|frame| frame.message)
+    }
+
+    fn write(&mut self, message: &Message) -> Result<(), MessageError> {
+        self.send(pack(message)?)
+    }
+
+    fn read_frame(&mut self) -> Result<Frame, MessageError> {
+        match self.receiver.try_recv() {
+            Ok(frame) => unpack_frame(&frame),
+            Err(TryRecvError::Empty) => Err(MessageError::EmptyRead),
+            Err(TryRecvError::Disconnected) => {
+                Err(MessageError::ConnectionClosed(ConnectionType::InMemory))
+            }
+        }
+    }
+
+    fn write_frame(&mut self, frame: &Frame) -> Result<(), MessageError> {
+        self.send(pack_frame(frame)?)
+    }
+
+    fn shutdown(&mut self) -> Result<(), MessageError> {
+        self.sender
+            .take()
+            .map(drop)
+            .ok_or(MessageError::ConnectionClosed(ConnectionType::InMemory))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PlayerId;
+
+    #[test]
+    fn messages_travel_both_ways() {
+        let (mut client, mut server) = memory_stream_pair();
+        assert!(matches!(client.read(), Err(MessageError::EmptyRead)));
+
+        client.write(&Message::RequestHint).unwrap();
+        assert!(matches!(server.read(), Ok(Message::RequestHint)));
+
+        server
+            .write(&Message::PlayerId(PlayerId::from_static("player0001")))
+            .unwrap();
+        match client.read() {
+            Ok(Message::PlayerId(player_id)) => assert_eq!(player_id, "player0001"),
+            message => panic!("message should be player id, got {:?}", message),
+        }
+    }
+
+    #[test]
+    fn shutdown_disconnects_other_end() {
+        let (mut client, mut server) = memory_stream_pair();
+        client.write(&Message::GameStart).unwrap();
+        client.shutdown().unwrap();
+
+        assert!(matches!(server.read(), Ok(Message::GameStart)));
+        assert!(matches!(
+            server.read(),
+            Err(MessageError::ConnectionClosed(ConnectionType::InMemory))
+        ));
+        assert!(client.write(&Message::GameStart).is_err());
+        assert!(client.shutdown().is_err());
+    }
+}
@@ -0,0 +1,271 @@
+use crate::helpers::{from_hex, to_hex};
+use crate::traits::Streamable;
+use crate::{
+    pack_with_request_id, unpack_frame, Frame, Message, MessageError, Password, HEADERS_LEN,
+};
+use std::io::{BufRead, Write};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    Sent,
+    Received,
+}
+
+impl Direction {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Direction::Sent => "sent",
+            Direction::Received => "received",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct RecordedMessage {
+    pub elapsed: Duration,
+    pub direction: Direction,
+    pub request_id: Option<u32>,
+    pub message: Message,
+}
+
+/// Streamable wrapper writing every read and written message to `output`.
+///
+/// Each line holds microseconds since the stream was created, the direction and the
+/// hex encoded frame, followed by a readable form of the message. Frames are stored
+/// as sent, except logins whose password is blanked.
+pub struct RecordingStream<S: Streamable> {
+    stream: S,
+    output: Box<dyn Write + Send>,
+    started: Instant,
+}
+
+impl<S: Streamable> RecordingStream<S> {
+    pub fn new(stream: S, output: impl Write + Send + 'static) -> Self {
+        RecordingStream {
+            stream,
+            output: Box::new(output),
+            started: Instant::now(),
+        }
+    }
+
+    fn record(&mut self, direction: Direction, request_id: Option<u32>, message: &Message) {
+        let blank_password = Message::Password(Password::default());
+        let message = match message {
+            Message::Password(_) => &blank_password,
+            message => message,
+        };
+        // messages that can not be packed have no frame to record
+        let Ok(frame) = pack_with_request_id(message, request_id) else {
+            return;
+        };
+        let line = format!(
+            "{} {} {} {:?}\n",
+            self.started.elapsed().as_micros(),
+            direction.as_str(),
+            to_hex(&frame),
+            message
+        );
+
+        // recording must never break the game, so write errors are ignored
+        let _ = self
+            .output
+            .write_all(line.as_bytes())
+            .and_then(|_| self.output.flush());
+    }
+}
+
+impl<S: Streamable> Streamable for RecordingStream<S> {
+    fn read(&mut self) -> Result<Message, MessageError> {
+        let message = self.stream.read()?;
+        self.record(Direction::Received, None, &message);
+
+        Ok(message)
+    }
+
+    fn write(&mut self, message: &Message) -> Result<(), MessageError> {
+        self.stream.write(message)?;
+        self.record(Direction::Sent, None, message);
+
+        Ok(())
+    }
+
+    fn read_frame(&mut self) -> Result<Frame, MessageError> {
+        let frame = self.stream.read_frame()?;
+        self.record(Direction::Received, frame.request_id, &frame.message);
+
+        Ok(frame)
+    }
+
+    fn write_frame(&mut self, frame: &Frame) -> Result<(), MessageError> {
+        self.stream.write_frame(frame)?;
+        self.record(Direction::Sent, frame.request_id, &frame.message);
+
+        Ok(())
+    }
+
+    fn shutdown(&mut self) -> Result<(), MessageError> {
+        self.stream.shutdown()
+    }
+}
+
+fn parse_line(line_number: usize, line: &str) -> Result<RecordedMessage, MessageError> {
+    let invalid = |reason| MessageError::InvalidRecording {
+        line: line_number,
+        reason,
+    };
+    let mut fields = line.split_whitespace();
+
+    let elapsed = fields
+        .next()
+        .and_then(|elapsed| elapsed.parse::<u64>().ok())
+        .map(Duration::from_micros)
+        .ok_or(invalid("Invalid timestamp"))?;
+    let direction = match fields.next() {
+        Some("sent") => Direction::Sent,
+        Some("received") => Direction::Received,
+        _ => return Err(invalid("Invalid direction")),
+    };
+    let frame = fields
+        .next()
+        .and_then(from_hex)
+        .filter(|frame| frame.len() > HEADERS_LEN)
+        .ok_or(invalid("Invalid frame"))?;
+
+    let Frame {
+        request_id,
+        message,
+    } = unpack_frame(&frame)?;
+
+    Ok(RecordedMessage {
+        elapsed,
+        direction,
+        request_id,
+        message,
+    })
+}
+
+/// Reads a recording written by `RecordingStream`, skipping empty lines.
+pub fn load_recording(reader: impl BufRead) -> Result<Vec<RecordedMessage>, MessageError> {
+    reader
+        .lines()
+        .map_while(Result::ok)
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| parse_line(index + 1, &line))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ConnectionType, PlayerId};
+    use std::collections::VecDeque;
+    use std::io::Cursor;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct QueueStream {
+        incoming: VecDeque<Message>,
+    }
+
+    impl Streamable for QueueStream {
+        fn read(&mut self) -> Result<Message, MessageError> {
+            self.incoming
+                .pop_front()
+                .ok_or(MessageError::ConnectionClosed(ConnectionType::Tcp))
+        }
+
+        fn write(&mut self, _message: &Message) -> Result<(), MessageError> {
+            Ok(())
+        }
+
+        fn shutdown(&mut self) -> Result<(), MessageError> {
+            Ok(())
+        }
+    }
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn recorded_session_can_be_loaded() {
+        let buffer = SharedBuffer::default();
+        let mut stream = QueueStream::default();
+        stream
+            .incoming
+            .push_back(Message::PlayerId(PlayerId::from_static("player0001")));
+        let mut stream = RecordingStream::new(stream, buffer.clone());
+
+        stream.write(&Message::GameStart).unwrap();
+        stream.read().unwrap();
+        assert!(stream.read().is_err());
+
+        let recording = buffer.0.lock().unwrap().clone();
+        let recorded = load_recording(Cursor::new(recording)).unwrap();
+
+        assert_eq!(recorded.len(), 2);
+        assert_eq!(recorded[0].direction, Direction::Sent);
+        assert!(matches!(recorded[0].message, Message::GameStart));
+        assert_eq!(recorded[1].direction, Direction::Received);
+        match &recorded[1].message {
+            Message::PlayerId(player_id) => assert_eq!(player_id, "player0001"),
+            message => panic!("message should be player id, got {:?}", message),
+        }
+        assert!(recorded[0].elapsed <= recorded[1].elapsed);
+    }
+
+    #[test]
+    fn passwords_are_blanked() {
+        let buffer = SharedBuffer::default();
+        let mut stream = RecordingStream::new(QueueStream::default(), buffer.clone());
+        let password = || {
+            Message::Password(Password {
+                password: String::from("secret"),
+            })
+        };
+
+        stream.write(&password()).unwrap();
+        stream
+            .write_frame(&Frame {
+                request_id: Some(3),
+                message: password(),
+            })
+            .unwrap();
+
+        let recording = buffer.0.lock().unwrap().clone();
+        assert!(!String::from_utf8_lossy(&recording).contains(&to_hex(b"secret")));
+        let recorded = load_recording(Cursor::new(recording)).unwrap();
+        assert_eq!(recorded.len(), 2);
+        assert_eq!(recorded[0].request_id, None);
+        assert_eq!(recorded[1].request_id, Some(3));
+        for recorded_message in recorded {
+            match recorded_message.message {
+                Message::Password(password) => assert!(password.password.is_empty()),
+                message => panic!("message should be password, got {:?}", message),
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_recording_line_is_rejected() {
+        let recording = "12 sent 474d45\n";
+        assert!(load_recording(Cursor::new(recording)).is_err());
+
+        let recording = "\n12 sideways 474d4500010a\n";
+        assert!(matches!(
+            load_recording(Cursor::new(recording)),
+            Err(MessageError::InvalidRecording { line: 2, .. })
+        ));
+    }
+}
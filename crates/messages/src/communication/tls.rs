@@ -0,0 +1,319 @@
+use super::framed::FramedStream;
+use super::json_lines::JsonLinesStream;
+use crate::helpers::to_hex;
+use crate::ConnectionType;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{ring, verify_tls12_signature, verify_tls13_signature, CryptoProvider};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer, ServerName, UnixTime};
+use rustls::{
+    ClientConfig, ClientConnection, DigitallySignedStruct, RootCertStore, ServerConfig,
+    ServerConnection, SignatureScheme, StreamOwned,
+};
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::net::{Shutdown, TcpStream};
+use std::path::Path;
+use std::sync::Arc;
+
+pub type TlsServerMessageStream = FramedStream<StreamOwned<ServerConnection, TcpStream>>;
+pub type TlsClientMessageStream = FramedStream<StreamOwned<ClientConnection, TcpStream>>;
+pub type TlsServerJsonStream = JsonLinesStream<StreamOwned<ServerConnection, TcpStream>>;
+
+/// Hex encoded SHA-256 of a DER certificate, as used for certificate pinning.
+pub fn certificate_fingerprint(certificate: &[u8]) -> String {
+    to_hex(::ring::digest::digest(&::ring::digest::SHA256, certificate).as_ref())
+}
+
+fn provider() -> Arc<CryptoProvider> {
+    Arc::new(ring::default_provider())
+}
+
+fn complete_handshake<C, S>(connection: &mut C, stream: &mut TcpStream) -> Result<(), String>
+where
+    C: std::ops::DerefMut<Target = rustls::ConnectionCommon<S>>,
+    S: rustls::SideData,
+{
+    while connection.is_handshaking() {
+        connection
+            .complete_io(stream)
+            .map_err(|e| format!("TLS handshake failed: {}", e))?;
+    }
+
+    Ok(())
+}
+
+fn close_tls<C, S>(tls_stream: &mut StreamOwned<C, TcpStream>) -> io::Result<()>
+where
+    C: std::ops::DerefMut<Target = rustls::ConnectionCommon<S>>,
+    S: rustls::SideData,
+{
+    tls_stream.conn.send_close_notify();
+    let _ = tls_stream.conn.write_tls(&mut tls_stream.sock);
+    tls_stream.sock.shutdown(Shutdown::Both)
+}
+
+pub struct ServerTls {
+    config: Arc<ServerConfig>,
+    /// Fingerprint of the served certificate, for clients pinning it.
+    pub fingerprint: String,
+}
+
+impl ServerTls {
+    fn new(
+        certificates: Vec<CertificateDer<'static>>,
+        key: PrivateKeyDer<'static>,
+    ) -> Result<Self, String> {
+        let fingerprint = certificates
+            .first()
+            .map(|certificate| certificate_fingerprint(certificate))
+            .ok_or("No certificate provided for TLS")?;
+        let config = ServerConfig::builder_with_provider(provider())
+            .with_safe_default_protocol_versions()
+            .and_then(|builder| {
+                builder
+                    .with_no_client_auth()
+                    .with_single_cert(certificates, key)
+            })
+            .map_err(|e| e.to_string())?;
+
+        Ok(ServerTls {
+            config: Arc::new(config),
+            fingerprint,
+        })
+    }
+
+    /// Loads a PEM certificate chain and private key.
+    pub fn from_pem_files(certificate_path: &Path, key_path: &Path) -> Result<Self, String> {
+        let certificates = rustls_pemfile::certs(&mut open_pem(certificate_path)?)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Invalid certificate {}: {}", certificate_path.display(), e))?;
+        let key = rustls_pemfile::private_key(&mut open_pem(key_path)?)
+            .map_err(|e| format!("Invalid key {}: {}", key_path.display(), e))?
+            .ok_or(format!("No private key found in {}", key_path.display()))?;
+
+        Self::new(certificates, key)
+    }
+
+    /// Generates a throwaway certificate for development, clients have to pin it.
+    pub fn self_signed(host_names: Vec<String>) -> Result<Self, String> {
+        let certified_key =
+            rcgen::generate_simple_self_signed(host_names).map_err(|e| e.to_string())?;
+        let key = PrivatePkcs8KeyDer::from(certified_key.key_pair.serialize_der());
+
+        Self::new(vec![certified_key.cert.der().clone()], key.into())
+    }
+
+    fn handshake(
+        &self,
+        mut stream: TcpStream,
+    ) -> Result<StreamOwned<ServerConnection, TcpStream>, String> {
+        let mut connection =
+            ServerConnection::new(self.config.clone()).map_err(|e| e.to_string())?;
+        complete_handshake(&mut connection, &mut stream)?;
+
+        Ok(StreamOwned::new(connection, stream))
+    }
+
+    /// Performs the handshake using the current blocking mode of `stream`.
+    pub fn accept(&self, stream: TcpStream) -> Result<TlsServerMessageStream, String> {
+        Ok(
+            FramedStream::new(self.handshake(stream)?, ConnectionType::Tcp)
+                .with_shutdown(close_tls),
+        )
+    }
+
+    /// Like `accept`, exchanging JSON lines instead of binary frames.
+    pub fn accept_json(&self, stream: TcpStream) -> Result<TlsServerJsonStream, String> {
+        Ok(
+            JsonLinesStream::new(self.handshake(stream)?, ConnectionType::Tcp)
+                .with_shutdown(close_tls),
+        )
+    }
+}
+
+pub struct ClientTls {
+    config: Arc<ClientConfig>,
+    server_name: ServerName<'static>,
+}
+
+impl ClientTls {
+    /// Trusts certificates issued by the CA certificates in the PEM file.
+    pub fn with_ca_file(server_name: &str, ca_path: &Path) -> Result<Self, String> {
+        let mut roots = RootCertStore::empty();
+        for certificate in rustls_pemfile::certs(&mut open_pem(ca_path)?) {
+            let certificate = certificate.map_err(|e| e.to_string())?;
+            roots.add(certificate).map_err(|e| e.to_string())?;
+        }
+
+        let config = ClientConfig::builder_with_provider(provider())
+            .with_safe_default_protocol_versions()
+            .map_err(|e| e.to_string())?
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+
+        Self::new(server_name, config)
+    }
+
+    /// Trusts only the certificate with the given SHA-256 fingerprint.
+    pub fn pinned(server_name: &str, fingerprint: &str) -> Result<Self, String> {
+        let fingerprint = fingerprint.replace(':', "").to_lowercase();
+        if fingerprint.len() != 64 || !fingerprint.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err("Pinned fingerprint must be a hex encoded SHA-256 hash".into());
+        }
+
+        let verifier = PinnedCertificateVerifier {
+            fingerprint,
+            provider: provider(),
+        };
+        let config = ClientConfig::builder_with_provider(provider())
+            .with_safe_default_protocol_versions()
+            .map_err(|e| e.to_string())?
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(verifier))
+            .with_no_client_auth();
+
+        Self::new(server_name, config)
+    }
+
+    fn new(server_name: &str, config: ClientConfig) -> Result<Self, String> {
+        let server_name = ServerName::try_from(server_name.to_owned())
+            .map_err(|_| format!("Invalid server name {}", server_name))?;
+
+        Ok(ClientTls {
+            config: Arc::new(config),
+            server_name,
+        })
+    }
+
+    /// Performs the handshake using the current blocking mode of `stream`.
+    pub fn connect(&self, mut stream: TcpStream) -> Result<TlsClientMessageStream, String> {
+        let mut connection = ClientConnection::new(self.config.clone(), self.server_name.clone())
+            .map_err(|e| e.to_string())?;
+        complete_handshake(&mut connection, &mut stream)?;
+
+        Ok(
+            FramedStream::new(StreamOwned::new(connection, stream), ConnectionType::Tcp)
+                .with_shutdown(close_tls),
+        )
+    }
+}
+
+fn open_pem(path: &Path) -> Result<BufReader<File>, String> {
+    File::open(path)
+        .map(BufReader::new)
+        .map_err(|e| format!("Can not open {}: {}", path.display(), e))
+}
+
+#[derive(Debug)]
+struct PinnedCertificateVerifier {
+    fingerprint: String,
+    provider: Arc<CryptoProvider>,
+}
+
+impl ServerCertVerifier for PinnedCertificateVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        if certificate_fingerprint(end_entity) != self.fingerprint {
+            return Err(rustls::Error::General(
+                "Server certificate does not match pinned fingerprint".into(),
+            ));
+        }
+
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        certificate: &CertificateDer<'_>,
+        signature: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(
+            message,
+            certificate,
+            signature,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        certificate: &CertificateDer<'_>,
+        signature: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(
+            message,
+            certificate,
+            signature,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Message, Streamable};
+    use std::net::TcpListener;
+    use std::thread;
+
+    fn serve_once(server_tls: ServerTls) -> (u16, thread::JoinHandle<Result<Message, String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let handle = thread::spawn(move || {
+            let (stream, _) = listener.accept().map_err(|e| e.to_string())?;
+            let mut tls_stream = server_tls.accept(stream)?;
+            tls_stream
+                .write(&Message::GameStart)
+                .map_err(|e| e.to_string())?;
+            tls_stream.read().map_err(|e| e.to_string())
+        });
+
+        (port, handle)
+    }
+
+    #[test]
+    fn pinned_client_exchanges_messages() {
+        let server_tls = ServerTls::self_signed(vec!["localhost".into()]).unwrap();
+        let client_tls = ClientTls::pinned("localhost", &server_tls.fingerprint).unwrap();
+        let (port, handle) = serve_once(server_tls);
+
+        let stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        let mut tls_stream = client_tls.connect(stream).unwrap();
+        assert!(matches!(tls_stream.read(), Ok(Message::GameStart)));
+        tls_stream.write(&Message::RequestHint).unwrap();
+
+        assert!(matches!(handle.join().unwrap(), Ok(Message::RequestHint)));
+    }
+
+    #[test]
+    fn client_rejects_certificate_with_other_fingerprint() {
+        let server_tls = ServerTls::self_signed(vec!["localhost".into()]).unwrap();
+        let other_tls = ServerTls::self_signed(vec!["localhost".into()]).unwrap();
+        let client_tls = ClientTls::pinned("localhost", &other_tls.fingerprint).unwrap();
+        let (port, handle) = serve_once(server_tls);
+
+        let stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        assert!(client_tls.connect(stream).is_err());
+        assert!(handle.join().unwrap().is_err());
+    }
+
+    #[test]
+    fn pinned_fingerprint_is_validated() {
+        assert!(ClientTls::pinned("localhost", "abc").is_err());
+        assert!(ClientTls::pinned("localhost", &"0".repeat(64)).is_ok());
+    }
+}
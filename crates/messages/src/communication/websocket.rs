@@ -0,0 +1,161 @@
+use crate::traits::Streamable;
+use crate::{pack, pack_frame, unpack_frame, ConnectionType, Frame, Message, MessageError};
+use std::io::{self, Read, Write};
+use tungstenite::handshake::derive_accept_key;
+use tungstenite::protocol::Role;
+use tungstenite::WebSocket;
+
+/// Value of the `Sec-WebSocket-Accept` header answering a client's `Sec-WebSocket-Key`.
+pub fn websocket_accept_key(key: &str) -> String {
+    derive_accept_key(key.trim().as_bytes())
+}
+
+/// Streamable over a WebSocket, each binary WebSocket message carries one packed frame.
+///
+/// Text, ping and pong messages are skipped, pongs are answered by the socket itself.
+pub struct WebSocketMessageStream<T: Read + Write> {
+    socket: WebSocket<T>,
+}
+
+impl<T: Read + Write> WebSocketMessageStream<T> {
+    pub fn new(socket: WebSocket<T>) -> Self {
+        WebSocketMessageStream { socket }
+    }
+
+    /// Wraps the server side of a connection whose HTTP upgrade was already answered.
+    /// `read_ahead` holds any bytes read past the end of the upgrade request.
+    pub fn upgraded(stream: T, read_ahead: Vec<u8>) -> Self {
+        Self::new(WebSocket::from_partially_read(
+            stream,
+            read_ahead,
+            Role::Server,
+            None,
+        ))
+    }
+
+    pub fn get_ref(&self) -> &T {
+        self.socket.get_ref()
+    }
+
+    fn send(&mut self, frame: Vec<u8>) -> Result<(), MessageError> {
+        match self.socket.send(tungstenite::Message::Binary(frame)) {
+            Ok(()) => Ok(()),
+            // the frame is queued and flushed on the next read or write
+            Err(error) if is_would_block(&error) => Ok(()),
+            Err(error) => Err(MessageError::InvalidWrite(
+                ConnectionType::WebSocket,
+                into_io_error(error),
+            )),
+        }
+    }
+}
+
+fn is_would_block(error: &tungstenite::Error) -> bool {
+    matches!(error, tungstenite::Error::Io(e) if e.kind() == io::ErrorKind::WouldBlock)
+}
+
+fn into_io_error(error: tungstenite::Error) -> io::Error {
+    match error {
+        tungstenite::Error::Io(error) => error,
+        tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed => {
+            io::Error::new(io::ErrorKind::NotConnected, error)
+        }
+        error => io::Error::new(io::ErrorKind::InvalidData, error),
+    }
+}
+
+impl<T: Read + Write + Send> Streamable for WebSocketMessageStream<T> {
+    fn read(&mut self) -> Result<Message, MessageError> {
+        self.read_frame().map(|frame| frame.message)
+    }
+
+    fn write(&mut self, message: &Message) -> Result<(), MessageError> {
+        self.send(pack(message)?)
+    }
+
+    fn read_frame(&mut self) -> Result<Frame, MessageError> {
+        loop {
+            match self.socket.read() {
+                Ok(tungstenite::Message::Binary(frame)) => return unpack_frame(&frame),
+                Ok(tungstenite::Message::Close(_)) | Err(tungstenite::Error::ConnectionClosed) => {
+                    return Err(MessageError::ConnectionClosed(ConnectionType::WebSocket))
+                }
+                Ok(_) => {}
+                Err(error) if is_would_block(&error) => return Err(MessageError::EmptyRead),
+                Err(error) => {
+                    return Err(MessageError::InvalidRead(
+                        ConnectionType::WebSocket,
+                        into_io_error(error),
+                    ))
+                }
+            }
+        }
+    }
+
+    fn write_frame(&mut self, frame: &Frame) -> Result<(), MessageError> {
+        self.send(pack_frame(frame)?)
+    }
+
+    fn shutdown(&mut self) -> Result<(), MessageError> {
+        let result = match self.socket.close(None) {
+            Ok(()) => self.socket.flush(),
+            result => result,
+        };
+
+        match result {
+            Ok(()) | Err(tungstenite::Error::ConnectionClosed) => Ok(()),
+            Err(error) if is_would_block(&error) => Ok(()),
+            Err(error) => Err(MessageError::InvalidWrite(
+                ConnectionType::WebSocket,
+                into_io_error(error),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PlayerId;
+    use std::net::{TcpListener, TcpStream};
+    use std::thread;
+
+    #[test]
+    fn accept_key_matches_rfc_example() {
+        assert_eq!(
+            websocket_accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+    }
+
+    #[test]
+    fn binary_frames_carry_messages() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let handle = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut server = WebSocketMessageStream::new(tungstenite::accept(stream).unwrap());
+            server
+                .write(&Message::PlayerId(PlayerId::from_static("player0001")))
+                .unwrap();
+            let message = server.read();
+            server.shutdown().unwrap();
+            message
+        });
+
+        let stream = TcpStream::connect(address).unwrap();
+        let (socket, _) = tungstenite::client(format!("ws://{}/ws", address), stream).unwrap();
+        let mut client = WebSocketMessageStream::new(socket);
+        match client.read() {
+            Ok(Message::PlayerId(player_id)) => assert_eq!(player_id, "player0001"),
+            message => panic!("message should be player id, got {:?}", message),
+        }
+        client.write(&Message::RequestHint).unwrap();
+
+        assert!(matches!(handle.join().unwrap(), Ok(Message::RequestHint)));
+        assert!(matches!(
+            client.read(),
+            Err(MessageError::ConnectionClosed(ConnectionType::WebSocket))
+        ));
+    }
+}
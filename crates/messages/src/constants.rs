@@ -1,3 +1,12 @@
+use crate::PlayerId;
+
 pub const ID_LENGTH: u8 = 10;
 pub const MESSAGE_PREFIX: &str = "GME";
+/// Prefix of frames carrying a request id between the header and the message type.
+pub const CORRELATED_MESSAGE_PREFIX: &str = "GMR";
+pub const REQUEST_ID_LEN: usize = 4;
 pub const HEADERS_LEN: usize = 5;
+/// Largest accepted frame body, message type included.
+pub const MAX_FRAME_LENGTH: usize = 16 * 1024;
+pub const MAX_CHAT_MESSAGE_LENGTH: usize = 200;
+pub const HOUSE_PLAYER_ID: PlayerId = PlayerId::from_static("HOUSE00000");
@@ -4,13 +4,6 @@ use rand::distributions::Alphanumeric;
 use rand::{thread_rng, Rng};
 use std::iter;
 
-pub fn get_string_slice_length(str: &str) -> u8 {
-    if str.len() > 255 {
-        panic!("string length can be max 255 characters");
-    }
-    str.len() as u8
-}
-
 pub fn get_random_id() -> String {
     let mut rng = thread_rng();
     iter::repeat(())
@@ -30,6 +23,21 @@ pub fn merge_u8(high_byte: u8, low_byte: u8) -> u16 {
     ((high_byte as u16) << 8) | (low_byte as u16)
 }
 
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+pub fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(hex.get(index..index + 2)?, 16).ok())
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -41,4 +49,13 @@ mod tests {
         let value = merge_u8(a, b);
         assert_eq!(value, 645);
     }
+
+    #[test]
+    fn hex_round_trip() {
+        let bytes = vec![0, 15, 16, 255];
+        assert_eq!(to_hex(&bytes), "000f10ff");
+        assert_eq!(from_hex("000f10ff"), Some(bytes));
+        assert_eq!(from_hex("0f1"), None);
+        assert_eq!(from_hex("zz"), None);
+    }
 }
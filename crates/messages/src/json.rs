@@ -0,0 +1,253 @@
+use crate::{fits_binary_format, Frame, Message, MessageError};
+use serde_json::Value;
+
+/// Longest JSON message accepted, keeps every message within the binary frame size.
+pub const MAX_JSON_MESSAGE_LENGTH: usize = 16 * 1024;
+
+/// JSON representation of a message, an object tagged by a snake case `type` field,
+/// e.g. `{"type":"player_id","player_id":"player0001"}`.
+pub fn to_json(message: &Message) -> String {
+    serde_json::to_string(message).expect("Messages always serialize to JSON")
+}
+
+/// Parses a message from JSON, rejecting messages the binary format can not carry.
+///
+/// Missing optional fields take their default value, unknown types become `Message::Unknown`.
+pub fn from_json(json: &str) -> Result<Message, MessageError> {
+    frame_from_json(json).map(|frame| frame.message)
+}
+
+/// Like `to_json`, with a `request_id` field when the frame has one.
+pub fn frame_to_json(frame: &Frame) -> String {
+    let Some(request_id) = frame.request_id else {
+        return to_json(&frame.message);
+    };
+
+    let mut json = serde_json::to_value(&frame.message).expect("Messages always serialize to JSON");
+    if let Value::Object(fields) = &mut json {
+        fields.insert("request_id".into(), request_id.into());
+    }
+
+    json.to_string()
+}
+
+/// Like `from_json`, keeping the optional `request_id` field.
+pub fn frame_from_json(json: &str) -> Result<Frame, MessageError> {
+    if json.len() > MAX_JSON_MESSAGE_LENGTH {
+        return Err(MessageError::FrameTooLong(json.len()));
+    }
+
+    let mut json: Value = serde_json::from_str(json).map_err(MessageError::InvalidJson)?;
+    let request_id = match json
+        .as_object_mut()
+        .and_then(|fields| fields.remove("request_id"))
+    {
+        None | Some(Value::Null) => None,
+        Some(request_id) => Some(
+            request_id
+                .as_u64()
+                .and_then(|request_id| u32::try_from(request_id).ok())
+                .ok_or(MessageError::decode(
+                    0,
+                    "Invalid request id in JSON message",
+                ))?,
+        ),
+    };
+
+    let message = serde_json::from_value(json).map_err(MessageError::InvalidJson)?;
+    if !fits_binary_format(&message) {
+        return Err(MessageError::decode(
+            0,
+            "JSON message has too long texts or lists",
+        ));
+    }
+
+    Ok(Frame {
+        request_id,
+        message,
+    })
+}
+
+// Variants wrapping plain values are written as objects with a named field.
+
+pub(crate) mod player_not_available {
+    use crate::PlayerId;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct PlayerNotAvailable {
+        player_id: PlayerId,
+    }
+
+    pub fn serialize<S: Serializer>(
+        player_id: &PlayerId,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        PlayerNotAvailable {
+            player_id: *player_id,
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<PlayerId, D::Error> {
+        PlayerNotAvailable::deserialize(deserializer).map(|message| message.player_id)
+    }
+}
+
+/// Same object as `player_not_available`, tagged `player_id`.
+pub(crate) use player_not_available as player_id;
+
+pub(crate) mod request_guessing_players {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct RequestGuessingPlayers {
+        #[serde(default)]
+        category: Option<String>,
+    }
+
+    pub fn serialize<S: Serializer>(
+        category: &Option<String>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        RequestGuessingPlayers {
+            category: category.clone(),
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<String>, D::Error> {
+        RequestGuessingPlayers::deserialize(deserializer).map(|message| {
+            message
+                .category
+                .filter(|category| !category.trim().is_empty())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        pack, unpack, ChatMessage, ChatScope, Difficulty, ErrorCode, ErrorMessage, PlayerId,
+        Riddle, Role,
+    };
+
+    #[test]
+    fn messages_round_trip_through_json() {
+        let riddle = Riddle {
+            sender: PlayerId::random(),
+            asking_player: PlayerId::random(),
+            guessing_player: PlayerId::random(),
+            message: "piano".into(),
+            hint: Some("88 keys".into()),
+            difficulty: Difficulty::Hard,
+            ..Default::default()
+        };
+        let messages = vec![
+            Message::Riddle(riddle),
+            Message::PlayerNotAvailable(PlayerId::random()),
+            Message::RequestGuessingPlayers(Some("music".into())),
+            Message::RequestGuessingPlayers(None),
+            Message::RegisterPlayerRole(Role::AskingPlayer.into()),
+            Message::ChatMessage(ChatMessage {
+                sender: PlayerId::random(),
+                scope: ChatScope::Game,
+                text: "hello".into(),
+            }),
+            Message::GameStart,
+            Message::RequestHouseRiddle,
+            Message::Error(ErrorMessage {
+                code: ErrorCode::RateLimited,
+                message: "slow down".into(),
+            }),
+        ];
+
+        for message in messages {
+            let parsed = from_json(&to_json(&message)).unwrap();
+            assert_eq!(pack(&parsed).unwrap(), pack(&message).unwrap());
+        }
+    }
+
+    #[test]
+    fn json_uses_snake_case_tags_and_values() {
+        assert_eq!(
+            to_json(&Message::PlayerNotAvailable(PlayerId::from_static(
+                "player0001"
+            ))),
+            r#"{"type":"player_not_available","player_id":"player0001"}"#
+        );
+        assert_eq!(
+            to_json(&Message::RegisterPlayerRole(Role::GuessingPlayer.into())),
+            r#"{"type":"register_player_role","role":"guessing","categories":[]}"#
+        );
+        assert_eq!(
+            to_json(&Message::Error(ErrorMessage {
+                code: ErrorCode::NotLoggedIn,
+                message: "Unauthorized action".into(),
+            })),
+            r#"{"type":"error","code":"not_logged_in","message":"Unauthorized action"}"#
+        );
+    }
+
+    #[test]
+    fn missing_fields_take_defaults() {
+        let message =
+            from_json(r#"{"type":"evaluation","guessed":true}"#).expect("Evaluation should parse");
+        match unpack(&pack(&message).unwrap()).unwrap() {
+            Message::Evaluation(evaluation) => {
+                assert!(evaluation.guessed);
+                assert_eq!(evaluation.hint, None);
+            }
+            message => panic!("message should be evaluation, got {:?}", message),
+        }
+
+        assert!(matches!(
+            from_json(r#"{"type":"request_guessing_players"}"#),
+            Ok(Message::RequestGuessingPlayers(None))
+        ));
+        assert!(matches!(
+            from_json(r#"{"type":"from_the_future"}"#),
+            Ok(Message::Unknown)
+        ));
+    }
+
+    #[test]
+    fn request_ids_round_trip_through_json() {
+        let json = frame_to_json(&Frame {
+            request_id: Some(42),
+            message: Message::RequestGuessingPlayers(None),
+        });
+        assert_eq!(
+            json,
+            r#"{"category":null,"request_id":42,"type":"request_guessing_players"}"#
+        );
+        assert!(matches!(
+            frame_from_json(&json),
+            Ok(Frame {
+                request_id: Some(42),
+                message: Message::RequestGuessingPlayers(None),
+            })
+        ));
+
+        assert_eq!(
+            frame_to_json(&Message::GameStart.into()),
+            r#"{"type":"game_start"}"#
+        );
+        assert!(frame_from_json(r#"{"type":"game_start","request_id":-1}"#).is_err());
+    }
+
+    #[test]
+    fn json_the_binary_format_can_not_carry_is_rejected() {
+        assert!(from_json("not json").is_err());
+        assert!(from_json(r#"{"type":"player_id","player_id":"short"}"#).is_err());
+
+        let long_text = format!(
+            r#"{{"type":"chat_message","sender":"player0001","scope":"lobby","text":"{}"}}"#,
+            "a".repeat(256)
+        );
+        assert!(from_json(&long_text).is_err());
+    }
+}
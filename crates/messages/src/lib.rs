@@ -1,228 +1,306 @@
+mod codec;
 mod communication;
 mod constants;
 mod helpers;
+mod json;
 mod message_error;
 mod messages;
 mod traits;
 
-use constants::HEADERS_LEN;
-use helpers::split_u16;
+use constants::{HEADERS_LEN, REQUEST_ID_LEN};
+use helpers::{merge_u8, split_u16};
+use serde::{Deserialize, Serialize};
 
+pub use communication::frame_reader::FrameReader;
+pub use communication::framed::FramedStream;
+pub use communication::json_lines::{JsonLinesStream, TcpJsonStream};
+pub use communication::memory::{memory_stream_pair, MemoryMessageStream};
+pub use communication::recording::{load_recording, Direction, RecordedMessage, RecordingStream};
 pub use communication::tcp::TcpMessageStream;
+pub use communication::tls::{
+    certificate_fingerprint, ClientTls, ServerTls, TlsClientMessageStream, TlsServerJsonStream,
+    TlsServerMessageStream,
+};
 pub use communication::unix_socket::UnixMessageStream;
-pub use constants::MESSAGE_PREFIX;
+pub use communication::websocket::{websocket_accept_key, WebSocketMessageStream};
+pub use constants::{
+    CORRELATED_MESSAGE_PREFIX, HOUSE_PLAYER_ID, MAX_CHAT_MESSAGE_LENGTH, MAX_FRAME_LENGTH,
+    MESSAGE_PREFIX,
+};
 pub use helpers::get_random_id;
+pub use json::{frame_from_json, frame_to_json, from_json, to_json, MAX_JSON_MESSAGE_LENGTH};
 pub use message_error::MessageError;
-pub use messages::{Evaluation, OpponentSelected, Password, PlayerId, PlayerList, Riddle};
-pub use traits::{Packable, Streamable};
-
+pub use messages::{
+    ChatMessage, ChatScope, Difficulty, ErrorCode, ErrorMessage, Evaluation, GameOver,
+    GameOverReason, OpponentSelected, Password, PlayerId, PlayerList, PlayerRole, Riddle,
+};
+pub use messages_derive::Packable;
+pub use traits::{Packable, PackableField, Streamable};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ConnectionType {
     UnixSocket,
     Tcp,
+    InMemory,
+    WebSocket,
 }
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum Role {
+    #[serde(rename = "asking")]
     AskingPlayer,
+    #[serde(rename = "guessing")]
     GuessingPlayer,
 }
 
+/// JSON form is an object tagged by a snake case `type`, see `to_json`. Packed as the
+/// message tag followed by the packed body.
+#[derive(Debug, Serialize, Deserialize, Packable)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum Message {
+    #[message(tag = 0)]
     Riddle(Riddle),
+    #[message(tag = 1)]
     Evaluation(Evaluation),
+    #[message(tag = 2)]
     OpponentSelected(OpponentSelected),
+    #[message(tag = 3)]
     Password(Password),
+    #[message(tag = 4)]
     PlayerList(PlayerList),
-    PlayerNotAvailable(String),
-    RegisterPlayerRole(Role),
-    RequestGuessingPlayers,
+    #[serde(with = "json::player_not_available")]
+    #[message(tag = 5)]
+    PlayerNotAvailable(PlayerId),
+    #[message(tag = 6)]
+    RegisterPlayerRole(PlayerRole),
+    #[serde(with = "json::request_guessing_players")]
+    #[message(tag = 8)]
+    #[packable(with = codec::optional_rest)]
+    RequestGuessingPlayers(Option<String>),
+    #[serde(with = "json::player_id")]
+    #[message(tag = 7)]
     PlayerId(PlayerId),
-    Unknown,
+    #[message(tag = 9)]
     GameStart,
+    #[message(tag = 11)]
+    ChatMessage(ChatMessage),
+    #[message(tag = 12)]
+    RequestHint,
+    #[message(tag = 13)]
+    GameOver(GameOver),
+    #[message(tag = 14)]
+    RequestHouseRiddle,
+    #[message(tag = 15)]
+    Error(ErrorMessage),
+    #[serde(other)]
+    #[message(tag = 10, other)]
+    Unknown,
 }
 
-pub fn pack(message: &Message) -> Vec<u8> {
-    let mut result = Vec::new();
-    result.extend_from_slice(MESSAGE_PREFIX.as_bytes());
+/// Message together with the optional request id of its frame.
+///
+/// Responses echo the request id of the request they answer, messages without one are
+/// sent in plain `MESSAGE_PREFIX` frames.
+#[derive(Debug)]
+pub struct Frame {
+    pub request_id: Option<u32>,
+    pub message: Message,
+}
 
-    match message {
-        Message::Riddle(riddle) => {
-            let message_body = riddle.pack();
-            let raw_message_length = (message_body.len() + 1) as u16;
-            let (high, low) = split_u16(raw_message_length);
-            result.push(high);
-            result.push(low);
-            result.push(0);
-            result.extend_from_slice(&message_body);
+impl From<Message> for Frame {
+    fn from(message: Message) -> Self {
+        Frame {
+            request_id: None,
+            message,
         }
+    }
+}
 
-        Message::Evaluation(evaluation) => {
-            let message_body = evaluation.pack();
-            let raw_message_length = (message_body.len() + 1) as u16;
-            let (high, low) = split_u16(raw_message_length);
-            result.push(high);
-            result.push(low);
-            result.push(1);
-            result.extend_from_slice(&message_body);
-        }
+/// Validated frame header, see `parse_header`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameHeader {
+    /// Length of the body following the header, request id included.
+    pub length: usize,
+    pub has_request_id: bool,
+}
 
-        Message::OpponentSelected(opponent_selected) => {
-            let message_body = opponent_selected.pack();
-            let raw_message_length = (message_body.len() + 1) as u16;
-            let (high, low) = split_u16(raw_message_length);
-            result.push(high);
-            result.push(low);
-            result.push(2);
-            result.extend_from_slice(&message_body);
-        }
-        Message::Password(password) => {
-            let message_body = password.pack();
-            let raw_message_length = (message_body.len() + 1) as u16;
-            let (high, low) = split_u16(raw_message_length);
-            result.push(high);
-            result.push(low);
-            result.push(3);
-            result.extend_from_slice(&message_body);
-        }
-        Message::PlayerList(player_list) => {
-            let message_body = player_list.pack();
-            let raw_message_length = (message_body.len() + 1) as u16;
-            let (high, low) = split_u16(raw_message_length);
-            result.push(high);
-            result.push(low);
-            result.push(4);
-            result.extend_from_slice(&message_body);
-        }
+fn is_short(text: &str) -> bool {
+    text.len() <= u8::MAX as usize
+}
 
-        Message::PlayerNotAvailable(id) => {
-            let raw_message_length = (id.as_bytes().len() + 1) as u16;
-            let (high, low) = split_u16(raw_message_length);
-            result.push(high);
-            result.push(low);
-            result.push(5);
-            result.extend_from_slice(id.as_bytes());
-        }
-        Message::RegisterPlayerRole(role) => {
-            let role_id: u8 = match role {
-                Role::AskingPlayer => 1,
-                Role::GuessingPlayer => 0,
-            };
-
-            result.push(0);
-            result.push(2);
-            result.push(6);
-            result.push(role_id);
-        }
-        Message::PlayerId(player_id) => {
-            let message_body = player_id.pack();
-            let raw_message_length = (message_body.len() + 1) as u16;
-            let (high, low) = split_u16(raw_message_length);
-            result.push(high);
-            result.push(low);
-            result.push(7);
-            result.extend_from_slice(&message_body);
-        }
-        Message::RequestGuessingPlayers => {
-            result.push(0);
-            result.push(1);
-            result.push(8);
-        }
-        Message::GameStart => {
-            result.push(0);
-            result.push(1);
-            result.push(9);
+/// Length prefixed texts and lists have to be at most 255 long, so the message can be packed
+/// and messages received as JSON can be forwarded to binary clients. Ids are validated while
+/// parsing, see `PlayerId`.
+pub fn fits_binary_format(message: &Message) -> bool {
+    match message {
+        Message::Riddle(riddle) => {
+            is_short(&riddle.message)
+                && riddle.hint.as_deref().is_none_or(is_short)
+                && is_short(&riddle.category)
         }
-        Message::Unknown => {
-            result.push(0);
-            result.push(1);
-            result.push(10);
+        Message::PlayerList(player_list) => player_list.opponent_ids.len() <= u8::MAX as usize,
+        Message::RegisterPlayerRole(player_role) => {
+            player_role.categories.len() <= u8::MAX as usize
+                && player_role
+                    .categories
+                    .iter()
+                    .all(|category| is_short(category))
         }
-    };
-
-    result
+        Message::ChatMessage(chat_message) => is_short(&chat_message.text),
+        Message::GameOver(game_over) => is_short(&game_over.answer),
+        _ => true,
+    }
 }
 
-pub fn unpack(message: &[u8]) -> Result<Message, MessageError> {
-    unpack_without_headers(&message[HEADERS_LEN..])
+/// Packs the message in a `MESSAGE_PREFIX` frame, failing on texts over 255 bytes and
+/// bodies over `MAX_FRAME_LENGTH`.
+pub fn pack(message: &Message) -> Result<Vec<u8>, MessageError> {
+    let message_body = message.pack()?;
+    if message_body.len() > MAX_FRAME_LENGTH {
+        return Err(MessageError::FrameTooLong(message_body.len()));
+    }
+    let (high, low) = split_u16(message_body.len() as u16);
+
+    let mut result = Vec::with_capacity(HEADERS_LEN + message_body.len());
+    result.extend_from_slice(MESSAGE_PREFIX.as_bytes());
+    result.push(high);
+    result.push(low);
+    result.extend_from_slice(&message_body);
+
+    Ok(result)
 }
 
-pub fn unpack_without_headers(message: &[u8]) -> Result<Message, MessageError> {
-    match message[0] {
-        0 => {
-            let mut riddle = Riddle::default();
-            riddle.unpack(&message[1..])?;
-            Ok(Message::Riddle(riddle))
-        }
-        1 => {
-            let mut evaluation = Evaluation::default();
-            evaluation.unpack(&message[1..])?;
+/// Packs the message, in a `CORRELATED_MESSAGE_PREFIX` frame when it has a request id.
+pub fn pack_frame(frame: &Frame) -> Result<Vec<u8>, MessageError> {
+    pack_with_request_id(&frame.message, frame.request_id)
+}
 
-            Ok(Message::Evaluation(evaluation))
-        }
-        2 => {
-            let mut opponent = OpponentSelected::default();
-            opponent.unpack(&message[1..])?;
+pub(crate) fn pack_with_request_id(
+    message: &Message,
+    request_id: Option<u32>,
+) -> Result<Vec<u8>, MessageError> {
+    let packed = pack(message)?;
+    let Some(request_id) = request_id else {
+        return Ok(packed);
+    };
 
-            Ok(Message::OpponentSelected(opponent))
-        }
-        3 => {
-            let mut password = Password::default();
-            password.unpack(&message[1..])?;
+    let body = &packed[HEADERS_LEN..];
+    let length = REQUEST_ID_LEN + body.len();
+    if length > MAX_FRAME_LENGTH {
+        return Err(MessageError::FrameTooLong(length));
+    }
+    let (high, low) = split_u16(length as u16);
+    let mut result = Vec::with_capacity(packed.len() + REQUEST_ID_LEN);
+    result.extend_from_slice(CORRELATED_MESSAGE_PREFIX.as_bytes());
+    result.push(high);
+    result.push(low);
+    result.extend_from_slice(&request_id.to_be_bytes());
+    result.extend_from_slice(body);
+
+    Ok(result)
+}
 
-            Ok(Message::Password(password))
-        }
-        4 => {
-            let mut player_list = PlayerList::default();
-            player_list.unpack(&message[1..])?;
+/// Validates a frame header, returning the length of the body that follows it.
+pub fn parse_header(header: &[u8; HEADERS_LEN]) -> Result<FrameHeader, MessageError> {
+    let (prefix, length) = header.split_at(MESSAGE_PREFIX.len());
+    let has_request_id = if prefix == MESSAGE_PREFIX.as_bytes() {
+        false
+    } else if prefix == CORRELATED_MESSAGE_PREFIX.as_bytes() {
+        true
+    } else {
+        return Err(MessageError::InvalidPrefix);
+    };
 
-            Ok(Message::PlayerList(player_list))
-        }
-        5 => {
-            let disconnected_id = String::from_utf8(message[1..].to_vec()).map_err(|_| {
-                MessageError::BadUnpack("Could not convert bytes to disconnected player id")
-            })?;
+    match merge_u8(length[0], length[1]) as usize {
+        0 => Err(MessageError::EmptyFrame),
+        length if length > MAX_FRAME_LENGTH => Err(MessageError::FrameTooLong(length)),
+        length => Ok(FrameHeader {
+            length,
+            has_request_id,
+        }),
+    }
+}
 
-            Ok(Message::PlayerNotAvailable(disconnected_id))
-        }
-        6 => {
-            let message_type = message[1];
+/// Unpacks one complete frame, rejecting invalid headers, truncated bodies and trailing bytes.
+pub fn unpack(message: &[u8]) -> Result<Message, MessageError> {
+    unpack_frame(message).map(|frame| frame.message)
+}
 
-            let mut role = Role::AskingPlayer;
-            if message_type == 0 {
-                role = Role::GuessingPlayer;
-            }
+/// Like `unpack`, keeping the request id of the frame.
+pub fn unpack_frame(message: &[u8]) -> Result<Frame, MessageError> {
+    let Some((header, body)) = message.split_first_chunk::<HEADERS_LEN>() else {
+        return Err(MessageError::TruncatedFrame {
+            expected: HEADERS_LEN,
+            actual: message.len(),
+        });
+    };
 
-            Ok(Message::RegisterPlayerRole(role))
-        }
-        7 => {
-            let mut player_id = PlayerId::default();
-            player_id.unpack(&message[1..])?;
+    let header = parse_header(header)?;
+    let length = header.length;
+    if body.len() < length {
+        return Err(MessageError::TruncatedFrame {
+            expected: length,
+            actual: body.len(),
+        });
+    }
+    if body.len() > length {
+        return Err(MessageError::Decode {
+            message_type: body.first().copied(),
+            offset: HEADERS_LEN + length,
+            reason: "Unexpected bytes after frame",
+        });
+    }
 
-            Ok(Message::PlayerId(player_id))
-        }
-        8 => Ok(Message::RequestGuessingPlayers),
-        9 => Ok(Message::GameStart),
+    unpack_frame_body(&header, body)
+}
 
-        _ => Ok(Message::Unknown),
+/// Unpacks the body following a header validated by `parse_header`.
+pub fn unpack_frame_body(header: &FrameHeader, body: &[u8]) -> Result<Frame, MessageError> {
+    if !header.has_request_id {
+        return unpack_without_headers(body)
+            .map(Frame::from)
+            .map_err(|error| error.within(HEADERS_LEN, None));
     }
+
+    let Some((request_id, message)) = body.split_first_chunk::<REQUEST_ID_LEN>() else {
+        return Err(MessageError::decode(
+            HEADERS_LEN,
+            "Frame is missing its request id",
+        ));
+    };
+
+    Ok(Frame {
+        request_id: Some(u32::from_be_bytes(*request_id)),
+        message: unpack_without_headers(message)
+            .map_err(|error| error.within(HEADERS_LEN + REQUEST_ID_LEN, None))?,
+    })
+}
+
+/// Unpacks a message type byte and its body, decode failures are located relative to the
+/// type byte.
+pub fn unpack_without_headers(message: &[u8]) -> Result<Message, MessageError> {
+    let mut unpacked = Message::Unknown;
+    unpacked.unpack(message)?;
+
+    Ok(unpacked)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use helpers::get_random_id;
 
     #[test]
     fn test_pack_unpack_riddle() {
         let riddle = Riddle {
-            sender: get_random_id(),
-            asking_player: get_random_id(),
-            guessing_player: get_random_id(),
+            sender: PlayerId::random(),
+            asking_player: PlayerId::random(),
+            guessing_player: PlayerId::random(),
             message: String::from(""),
             hint: None,
+            ..Default::default()
         };
 
-        let bytes = pack(&Message::Riddle(riddle));
+        let bytes = pack(&Message::Riddle(riddle)).unwrap();
         let message = unpack(&bytes).unwrap();
 
         if let Message::Riddle(_) = message {
@@ -233,7 +311,7 @@ mod tests {
     #[test]
     fn test_pack_unpack_evaluation() {
         let riddle = Evaluation::default();
-        let bytes = pack(&Message::Evaluation(riddle));
+        let bytes = pack(&Message::Evaluation(riddle)).unwrap();
         let message = unpack(&bytes).unwrap();
 
         if let Message::Evaluation(_) = message {
@@ -245,11 +323,11 @@ mod tests {
     #[test]
     fn test_pack_unpack_opponent_selected() {
         let opponent_selected = OpponentSelected {
-            guessing_player: get_random_id(),
-            asking_player: get_random_id(),
+            guessing_player: PlayerId::random(),
+            asking_player: PlayerId::random(),
         };
 
-        let bytes = pack(&Message::OpponentSelected(opponent_selected));
+        let bytes = pack(&Message::OpponentSelected(opponent_selected)).unwrap();
         let message = unpack(&bytes).unwrap();
 
         if let Message::OpponentSelected(_) = message {
@@ -261,7 +339,7 @@ mod tests {
     #[test]
     fn test_pack_unpack_player_list() {
         let player_list = PlayerList::default();
-        let bytes = pack(&Message::PlayerList(player_list));
+        let bytes = pack(&Message::PlayerList(player_list)).unwrap();
         let message = unpack(&bytes).unwrap();
 
         if let Message::PlayerList(_) = message {
@@ -272,8 +350,8 @@ mod tests {
 
     #[test]
     fn test_pack_unpack_disconnected_player() {
-        let player = get_random_id();
-        let bytes = pack(&Message::PlayerNotAvailable(player.clone()));
+        let player = PlayerId::random();
+        let bytes = pack(&Message::PlayerNotAvailable(player)).unwrap();
         let message = unpack(&bytes).unwrap();
 
         if let Message::PlayerNotAvailable(player_unwrapped) = message {
@@ -285,11 +363,11 @@ mod tests {
 
     #[test]
     fn test_pack_unpack_register_player_guessing_role() {
-        let bytes = pack(&Message::RegisterPlayerRole(Role::GuessingPlayer));
+        let bytes = pack(&Message::RegisterPlayerRole(Role::GuessingPlayer.into())).unwrap();
         let message = unpack(&bytes).unwrap();
 
-        if let Message::RegisterPlayerRole(role) = message {
-            match role {
+        if let Message::RegisterPlayerRole(player_role) = message {
+            match player_role.role {
                 Role::AskingPlayer => panic!("Role should be GuessingPlayer"),
                 Role::GuessingPlayer => {}
             }
@@ -300,11 +378,11 @@ mod tests {
 
     #[test]
     fn test_pack_unpack_register_player_asking_role() {
-        let bytes = pack(&Message::RegisterPlayerRole(Role::AskingPlayer));
+        let bytes = pack(&Message::RegisterPlayerRole(Role::AskingPlayer.into())).unwrap();
         let message = unpack(&bytes).unwrap();
 
-        if let Message::RegisterPlayerRole(role) = message {
-            match role {
+        if let Message::RegisterPlayerRole(player_role) = message {
+            match player_role.role {
                 Role::AskingPlayer => {}
                 Role::GuessingPlayer => {
                     panic!("Role should be AskinPlayer")
@@ -317,15 +395,12 @@ mod tests {
 
     #[test]
     fn test_pack_unpack_player_id() {
-        let random_id = get_random_id();
-        let player = PlayerId {
-            player_id: random_id.clone(),
-        };
-        let bytes = pack(&Message::PlayerId(player));
+        let random_id = PlayerId::random();
+        let bytes = pack(&Message::PlayerId(random_id)).unwrap();
         let message = unpack(&bytes).unwrap();
 
         if let Message::PlayerId(player_id) = message {
-            assert_eq!(random_id, player_id.player_id);
+            assert_eq!(random_id, player_id);
         } else {
             panic!("message should be PlayerId");
         }
@@ -333,10 +408,23 @@ mod tests {
 
     #[test]
     fn test_pack_unpack_request_ids() {
-        let bytes = pack(&Message::RequestGuessingPlayers);
+        let bytes = pack(&Message::RequestGuessingPlayers(None)).unwrap();
         let message = unpack(&bytes).unwrap();
 
-        if let Message::RequestGuessingPlayers = message {
+        if let Message::RequestGuessingPlayers(category) = message {
+            assert_eq!(category, None);
+        } else {
+            panic!("message should be request ids");
+        }
+    }
+
+    #[test]
+    fn test_pack_unpack_request_ids_with_category() {
+        let bytes = pack(&Message::RequestGuessingPlayers(Some("nature".into()))).unwrap();
+        let message = unpack(&bytes).unwrap();
+
+        if let Message::RequestGuessingPlayers(category) = message {
+            assert_eq!(category.as_deref(), Some("nature"));
         } else {
             panic!("message should be request ids");
         }
@@ -344,7 +432,7 @@ mod tests {
 
     #[test]
     fn test_pack_unpack_game_start() {
-        let bytes = pack(&Message::GameStart);
+        let bytes = pack(&Message::GameStart).unwrap();
         let message = unpack(&bytes).unwrap();
 
         if let Message::GameStart = message {
@@ -352,9 +440,82 @@ mod tests {
             panic!("message should be unknown");
         }
     }
+    #[test]
+    fn test_pack_unpack_chat_message() {
+        let chat_message = ChatMessage {
+            sender: PlayerId::random(),
+            scope: ChatScope::Lobby,
+            text: String::from("Anyone up for a game?"),
+        };
+        let bytes = pack(&Message::ChatMessage(chat_message)).unwrap();
+        let message = unpack(&bytes).unwrap();
+
+        if let Message::ChatMessage(chat_message) = message {
+            assert_eq!(chat_message.text, "Anyone up for a game?");
+        } else {
+            panic!("message should be chat message");
+        }
+    }
+
+    #[test]
+    fn test_pack_unpack_request_hint() {
+        let bytes = pack(&Message::RequestHint).unwrap();
+        let message = unpack(&bytes).unwrap();
+
+        if let Message::RequestHint = message {
+        } else {
+            panic!("message should be request hint");
+        }
+    }
+
+    #[test]
+    fn test_pack_unpack_game_over() {
+        let game_over = GameOver {
+            winner: PlayerId::random(),
+            reason: GameOverReason::MaxGuessesReached,
+            answer: String::from("Galaxy"),
+        };
+        let bytes = pack(&Message::GameOver(game_over)).unwrap();
+        let message = unpack(&bytes).unwrap();
+
+        if let Message::GameOver(game_over) = message {
+            assert_eq!(game_over.answer, "Galaxy");
+        } else {
+            panic!("message should be game over");
+        }
+    }
+
+    #[test]
+    fn test_pack_unpack_request_house_riddle() {
+        let bytes = pack(&Message::RequestHouseRiddle).unwrap();
+        let message = unpack(&bytes).unwrap();
+
+        if let Message::RequestHouseRiddle = message {
+        } else {
+            panic!("message should be request house riddle");
+        }
+    }
+
+    #[test]
+    fn test_pack_unpack_error() {
+        let error_message = ErrorMessage {
+            code: ErrorCode::InvalidPassword,
+            message: String::from("Invalid login attempt"),
+        };
+        let bytes = pack(&Message::Error(error_message)).unwrap();
+        let message = unpack(&bytes).unwrap();
+
+        if let Message::Error(error_message) = message {
+            assert_eq!(error_message.code, ErrorCode::InvalidPassword);
+            assert_eq!(error_message.message, "Invalid login attempt");
+        } else {
+            panic!("message should be error");
+        }
+    }
+
     #[test]
     fn test_pack_unpack_unknown() {
-        let bytes = pack(&Message::Unknown);
+        let bytes = pack(&Message::Unknown).unwrap();
         let message = unpack(&bytes).unwrap();
 
         if let Message::Unknown = message {
@@ -365,7 +526,7 @@ mod tests {
 
     #[test]
     fn test_pack_unpack_unknown_different_sign() {
-        let mut bytes = pack(&Message::Unknown);
+        let mut bytes = pack(&Message::Unknown).unwrap();
         let last_idx = bytes.len() - 1;
         let num = bytes.get_mut(last_idx).unwrap();
         *num = 145;
@@ -376,4 +537,212 @@ mod tests {
             panic!("message should be Unknown for not recognized number");
         }
     }
+
+    fn sample_frames() -> Vec<Vec<u8>> {
+        let riddle = Riddle {
+            sender: PlayerId::random(),
+            asking_player: PlayerId::random(),
+            guessing_player: PlayerId::random(),
+            message: String::from("piano"),
+            hint: Some(String::from("88 keys")),
+            category: String::from("music"),
+            ..Default::default()
+        };
+        vec![
+            pack(&Message::Riddle(riddle)).unwrap(),
+            pack(&Message::Evaluation(Evaluation::default())).unwrap(),
+            pack(&Message::OpponentSelected(OpponentSelected {
+                guessing_player: PlayerId::random(),
+                asking_player: PlayerId::random(),
+            }))
+            .unwrap(),
+            pack(&Message::PlayerList(PlayerList {
+                opponent_ids: vec![PlayerId::random(), PlayerId::random()],
+            }))
+            .unwrap(),
+            pack(&Message::RegisterPlayerRole(PlayerRole {
+                role: Role::GuessingPlayer,
+                categories: vec![String::from("music")],
+            }))
+            .unwrap(),
+            pack(&Message::PlayerId(PlayerId::random())).unwrap(),
+            pack(&Message::ChatMessage(ChatMessage {
+                sender: PlayerId::random(),
+                scope: ChatScope::Game,
+                text: String::from("hello"),
+            }))
+            .unwrap(),
+            pack(&Message::GameOver(GameOver {
+                winner: PlayerId::random(),
+                answer: String::from("piano"),
+                ..Default::default()
+            }))
+            .unwrap(),
+            pack(&Message::Error(ErrorMessage {
+                code: ErrorCode::NoOpponent,
+                message: String::from("No game"),
+            }))
+            .unwrap(),
+        ]
+    }
+
+    #[test]
+    fn test_unpack_rejects_invalid_headers() {
+        assert!(matches!(
+            unpack(b"HTTP/1.1"),
+            Err(MessageError::InvalidPrefix)
+        ));
+        assert!(matches!(unpack(b"GME\0\0"), Err(MessageError::EmptyFrame)));
+        assert!(matches!(
+            unpack(b"GME\xff\xff\x09"),
+            Err(MessageError::FrameTooLong(0xffff))
+        ));
+        assert!(matches!(
+            unpack(b"GM"),
+            Err(MessageError::TruncatedFrame {
+                expected: HEADERS_LEN,
+                actual: 2
+            })
+        ));
+        assert!(matches!(
+            unpack(b"GME\0\x03\x07"),
+            Err(MessageError::TruncatedFrame {
+                expected: 3,
+                actual: 1
+            })
+        ));
+
+        let mut trailing = pack(&Message::GameStart).unwrap();
+        trailing.push(0);
+        assert!(matches!(
+            unpack(&trailing),
+            Err(MessageError::Decode {
+                message_type: Some(9),
+                offset: 6,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_pack_unpack_frame_with_request_id() {
+        let frame = Frame {
+            request_id: Some(0x0102_0304),
+            message: Message::RequestGuessingPlayers(Some(String::from("music"))),
+        };
+        let bytes = pack_frame(&frame).unwrap();
+        assert_eq!(&bytes[..HEADERS_LEN], b"GMR\0\x0a");
+        assert_eq!(
+            &bytes[HEADERS_LEN..HEADERS_LEN + REQUEST_ID_LEN],
+            &[1, 2, 3, 4]
+        );
+
+        match unpack_frame(&bytes).unwrap() {
+            Frame {
+                request_id: Some(0x0102_0304),
+                message: Message::RequestGuessingPlayers(Some(category)),
+            } => assert_eq!(category, "music"),
+            frame => panic!("frame should be request with id, got {:?}", frame),
+        }
+        assert!(matches!(
+            unpack(&bytes),
+            Ok(Message::RequestGuessingPlayers(_))
+        ));
+        assert_eq!(
+            pack_frame(&Message::GameStart.into()).unwrap(),
+            pack(&Message::GameStart).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_pack_rejects_oversized_fields() {
+        let riddle = Riddle {
+            message: "a".repeat(256),
+            ..Default::default()
+        };
+        assert!(matches!(
+            pack(&Message::Riddle(riddle)),
+            Err(MessageError::TextTooLong(256))
+        ));
+
+        let password = |length| {
+            Message::Password(Password {
+                password: "a".repeat(length),
+            })
+        };
+        assert!(pack(&password(MAX_FRAME_LENGTH - 1)).is_ok());
+        assert!(matches!(
+            pack(&password(MAX_FRAME_LENGTH)),
+            Err(MessageError::FrameTooLong(_))
+        ));
+        assert!(matches!(
+            pack(&password(70_000)),
+            Err(MessageError::FrameTooLong(70_001))
+        ));
+        assert!(matches!(
+            pack_frame(&Frame {
+                request_id: Some(1),
+                message: password(MAX_FRAME_LENGTH - 1),
+            }),
+            Err(MessageError::FrameTooLong(_))
+        ));
+    }
+
+    #[test]
+    fn test_unpack_frame_rejects_missing_request_id() {
+        assert!(matches!(
+            unpack_frame(b"GMR\0\x02\0\0"),
+            Err(MessageError::Decode {
+                message_type: None,
+                offset: HEADERS_LEN,
+                ..
+            })
+        ));
+        assert!(matches!(
+            unpack_frame(b"GMR\0\x04\0\0\0\x01"),
+            Err(MessageError::EmptyFrame)
+        ));
+    }
+
+    #[test]
+    fn test_unpack_without_headers_rejects_empty_body() {
+        assert!(matches!(
+            unpack_without_headers(&[]),
+            Err(MessageError::EmptyFrame)
+        ));
+    }
+
+    #[test]
+    fn test_unpack_never_panics_on_malformed_frames() {
+        use rand::Rng;
+
+        for frame in sample_frames() {
+            for length in 0..frame.len() {
+                assert!(unpack(&frame[..length]).is_err());
+
+                // a consistent header over a cut body reaches the message specific unpack
+                let mut body = frame[HEADERS_LEN.min(length)..length].to_vec();
+                let [high, low] = (body.len() as u16).to_be_bytes();
+                let mut shortened = vec![b'G', b'M', b'E', high, low];
+                shortened.append(&mut body);
+                let _ = unpack(&shortened);
+            }
+        }
+
+        let mut rng = rand::thread_rng();
+        for _ in 0..10_000 {
+            let length = rng.gen_range(0..64);
+            let mut frame: Vec<u8> = (0..length).map(|_| rng.gen()).collect();
+            let _ = unpack(&frame);
+            if frame.len() >= HEADERS_LEN {
+                let [high, low] = ((frame.len() - HEADERS_LEN) as u16).to_be_bytes();
+                frame[..HEADERS_LEN].copy_from_slice(&[b'G', b'M', b'E', high, low]);
+                frame[HEADERS_LEN..]
+                    .iter_mut()
+                    .take(1)
+                    .for_each(|t| *t %= 16);
+                let _ = unpack(&frame);
+            }
+        }
+    }
 }
@@ -1,31 +1,172 @@
-use crate::ConnectionType;
+use crate::constants::ID_LENGTH;
+use crate::{ConnectionType, CORRELATED_MESSAGE_PREFIX, MAX_FRAME_LENGTH, MESSAGE_PREFIX};
+use std::error::Error;
 use std::fmt;
 use std::fmt::{Debug, Display};
+use std::io;
 
 pub enum MessageError {
-    BadUnpack(&'static str),
+    /// Message body could not be decoded. `offset` counts from the start of the frame once
+    /// the message was unpacked through `unpack_frame_body`, `message_type` is set by then.
+    Decode {
+        message_type: Option<u8>,
+        offset: usize,
+        reason: &'static str,
+    },
     EmptyRead,
-    InvalidRead(ConnectionType),
-    InvalidWrite(ConnectionType),
+    /// Frame does not start with `MESSAGE_PREFIX` or `CORRELATED_MESSAGE_PREFIX`.
+    InvalidPrefix,
+    /// Frame header announces a body of zero bytes.
+    EmptyFrame,
+    /// Frame header announces a body longer than `MAX_FRAME_LENGTH`, a packed message would
+    /// need one or a JSON message is longer than `MAX_JSON_MESSAGE_LENGTH`.
+    FrameTooLong(usize),
+    /// Text packed with a one byte length is longer than 255 bytes.
+    TextTooLong(usize),
+    /// List packed with a one byte item count has more than 255 items.
+    ListTooLong(usize),
+    /// Frame ends before the announced number of bytes.
+    TruncatedFrame {
+        expected: usize,
+        actual: usize,
+    },
+    /// Player id is not `ID_LENGTH` alphanumeric characters.
+    InvalidPlayerId(String),
+    /// JSON message is malformed or does not describe a message.
+    InvalidJson(serde_json::Error),
+    /// Line of a recording, counted from 1, can not be parsed.
+    InvalidRecording {
+        line: usize,
+        reason: &'static str,
+    },
+    /// Peer closed the connection between two frames.
+    ConnectionClosed(ConnectionType),
+    InvalidRead(ConnectionType, io::Error),
+    InvalidWrite(ConnectionType, io::Error),
+}
+
+impl MessageError {
+    pub const fn decode(offset: usize, reason: &'static str) -> Self {
+        MessageError::Decode {
+            message_type: None,
+            offset,
+            reason,
+        }
+    }
+
+    /// Moves the offset of a decode failure by `shift` bytes and sets its message type
+    /// when still unknown, other errors are returned unchanged.
+    pub(crate) fn within(self, shift: usize, message_type: Option<u8>) -> Self {
+        match self {
+            MessageError::Decode {
+                message_type: known_type,
+                offset,
+                reason,
+            } => MessageError::Decode {
+                message_type: known_type.or(message_type),
+                offset: offset + shift,
+                reason,
+            },
+            error => error,
+        }
+    }
+
+    /// Kind of the underlying io error of a failed read or write.
+    pub fn io_kind(&self) -> Option<io::ErrorKind> {
+        match self {
+            Self::InvalidRead(_, error) | Self::InvalidWrite(_, error) => Some(error.kind()),
+            _ => None,
+        }
+    }
 }
 
 impl Display for MessageError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Self::BadUnpack(message) => write!(f, "Bad Unpack: {}", message),
+            Self::Decode {
+                message_type: Some(message_type),
+                offset,
+                reason,
+            } => write!(
+                f,
+                "Could not decode message of type {} at byte {}: {}",
+                message_type, offset, reason
+            ),
+            Self::Decode {
+                message_type: None,
+                offset,
+                reason,
+            } => write!(f, "Could not decode message at byte {}: {}", offset, reason),
             Self::EmptyRead => write!(f, ""),
-            Self::InvalidRead(connection_type) => match connection_type {
+            Self::InvalidPrefix => write!(
+                f,
+                "Frame does not start with {} or {}",
+                MESSAGE_PREFIX, CORRELATED_MESSAGE_PREFIX
+            ),
+            Self::EmptyFrame => write!(f, "Frame has no message type"),
+            Self::FrameTooLong(length) => write!(
+                f,
+                "Frame of {} bytes exceeds the limit of {} bytes",
+                length, MAX_FRAME_LENGTH
+            ),
+            Self::TextTooLong(length) => write!(
+                f,
+                "Text of {} bytes exceeds the limit of {} bytes",
+                length,
+                u8::MAX
+            ),
+            Self::ListTooLong(count) => write!(
+                f,
+                "List of {} items exceeds the limit of {} items",
+                count,
+                u8::MAX
+            ),
+            Self::TruncatedFrame { expected, actual } => write!(
+                f,
+                "Frame is truncated, expected {} bytes, got {}",
+                expected, actual
+            ),
+            Self::InvalidPlayerId(id) => write!(
+                f,
+                "Invalid player id {:?}, expected {} alphanumeric characters",
+                id, ID_LENGTH
+            ),
+            Self::InvalidJson(error) => write!(f, "Invalid JSON message: {}", error),
+            Self::InvalidRecording { line, reason } => {
+                write!(f, "Invalid recording line {}: {}", line, reason)
+            }
+            Self::ConnectionClosed(connection_type) => match connection_type {
+                ConnectionType::Tcp => write!(f, "TCP stream was closed."),
+                ConnectionType::UnixSocket => write!(f, "Unix socket was closed."),
+                ConnectionType::InMemory => write!(f, "In-memory stream was closed."),
+                ConnectionType::WebSocket => write!(f, "WebSocket was closed."),
+            },
+            Self::InvalidRead(connection_type, _) => match connection_type {
                 ConnectionType::Tcp => write!(f, "Could not read data from TCP stream."),
                 ConnectionType::UnixSocket => write!(f, "Could not read data from unix socket."),
+                ConnectionType::InMemory => write!(f, "Could not read data from in-memory stream."),
+                ConnectionType::WebSocket => write!(f, "Could not read data from WebSocket."),
             },
-            Self::InvalidWrite(connection_type) => match connection_type {
+            Self::InvalidWrite(connection_type, _) => match connection_type {
                 ConnectionType::Tcp => write!(f, "Could not write data to TCP stream."),
                 ConnectionType::UnixSocket => write!(f, "Could not write data to unix socket."),
+                ConnectionType::InMemory => write!(f, "Could not write data to in-memory stream."),
+                ConnectionType::WebSocket => write!(f, "Could not write data to WebSocket."),
             },
         }
     }
 }
 
+impl Error for MessageError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::InvalidRead(_, error) | Self::InvalidWrite(_, error) => Some(error),
+            Self::InvalidJson(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
 impl Debug for MessageError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", self)
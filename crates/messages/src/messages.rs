@@ -1,13 +1,21 @@
+mod chat_message;
+mod error_message;
 mod evaluation;
+mod game_over;
 mod opponent_selected;
 mod password;
 mod player_id;
 mod player_list;
+mod player_role;
 mod riddle;
 
+pub use chat_message::{ChatMessage, ChatScope};
+pub use error_message::{ErrorCode, ErrorMessage};
 pub use evaluation::Evaluation;
+pub use game_over::{GameOver, GameOverReason};
 pub use opponent_selected::OpponentSelected;
 pub use password::Password;
 pub use player_id::PlayerId;
 pub use player_list::PlayerList;
-pub use riddle::Riddle;
+pub use player_role::PlayerRole;
+pub use riddle::{Difficulty, Riddle};
@@ -0,0 +1,78 @@
+use crate::traits::PackableField;
+use crate::{MessageError, Packable, PlayerId};
+use serde::{Deserialize, Serialize};
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChatScope {
+    #[default]
+    Lobby,
+    Game,
+}
+
+impl PackableField for ChatScope {
+    fn pack_field(&self, packed: &mut Vec<u8>) -> Result<(), MessageError> {
+        packed.push(match self {
+            ChatScope::Lobby => 0,
+            ChatScope::Game => 1,
+        });
+        Ok(())
+    }
+
+    fn unpack_field(msg_bytes: &[u8], offset: &mut usize) -> Result<Self, MessageError> {
+        match u8::unpack_field(msg_bytes, offset)? {
+            0 => Ok(ChatScope::Lobby),
+            1 => Ok(ChatScope::Game),
+            _ => Err(MessageError::decode(*offset - 1, "Invalid chat scope")),
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize, Packable)]
+pub struct ChatMessage {
+    pub sender: PlayerId,
+    #[serde(default)]
+    pub scope: ChatScope,
+    #[serde(default)]
+    pub text: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::ID_LENGTH;
+
+    #[test]
+    fn pack_unpack_chat_message() {
+        let chat_message = ChatMessage {
+            sender: PlayerId::random(),
+            scope: ChatScope::Game,
+            text: String::from("Good luck!"),
+        };
+
+        let bytes = chat_message.pack().unwrap();
+        let mut check_instance = ChatMessage::default();
+        check_instance
+            .unpack(&bytes)
+            .expect("Unpacking should not fail");
+
+        assert_eq!(check_instance.sender, chat_message.sender);
+        assert_eq!(check_instance.scope, ChatScope::Game);
+        assert_eq!(check_instance.text, chat_message.text);
+    }
+
+    #[test]
+    fn unpack_chat_message_with_invalid_scope() {
+        let mut bytes = ChatMessage {
+            sender: PlayerId::random(),
+            scope: ChatScope::Lobby,
+            text: String::from("Hello"),
+        }
+        .pack()
+        .unwrap();
+        bytes[ID_LENGTH as usize] = 7;
+
+        let mut check_instance = ChatMessage::default();
+        assert!(check_instance.unpack(&bytes).is_err());
+    }
+}
@@ -0,0 +1,139 @@
+use crate::codec;
+use crate::traits::PackableField;
+use crate::{MessageError, Packable};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Reason of a rejection, the numeric values are part of the protocol and never reused.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    InvalidPassword,
+    NotLoggedIn,
+    WrongRole,
+    NoOpponent,
+    RateLimited,
+    InvalidMessage,
+    Internal,
+    /// Message names another player or is only sent by the server.
+    Forbidden,
+    /// Hint budget of the current game is spent.
+    NoHintsLeft,
+    /// Code added by a newer server.
+    #[default]
+    #[serde(other)]
+    Unknown,
+}
+
+impl ErrorCode {
+    pub fn as_u8(&self) -> u8 {
+        match self {
+            ErrorCode::InvalidPassword => 0,
+            ErrorCode::NotLoggedIn => 1,
+            ErrorCode::WrongRole => 2,
+            ErrorCode::NoOpponent => 3,
+            ErrorCode::RateLimited => 4,
+            ErrorCode::InvalidMessage => 5,
+            ErrorCode::Internal => 6,
+            ErrorCode::Forbidden => 7,
+            ErrorCode::NoHintsLeft => 8,
+            ErrorCode::Unknown => u8::MAX,
+        }
+    }
+
+    pub fn from_u8(code: u8) -> Self {
+        match code {
+            0 => ErrorCode::InvalidPassword,
+            1 => ErrorCode::NotLoggedIn,
+            2 => ErrorCode::WrongRole,
+            3 => ErrorCode::NoOpponent,
+            4 => ErrorCode::RateLimited,
+            5 => ErrorCode::InvalidMessage,
+            6 => ErrorCode::Internal,
+            7 => ErrorCode::Forbidden,
+            8 => ErrorCode::NoHintsLeft,
+            _ => ErrorCode::Unknown,
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let description = match self {
+            ErrorCode::InvalidPassword => "Invalid password",
+            ErrorCode::NotLoggedIn => "Not logged in",
+            ErrorCode::WrongRole => "Not allowed for the current role",
+            ErrorCode::NoOpponent => "No game in progress",
+            ErrorCode::RateLimited => "Too many messages",
+            ErrorCode::InvalidMessage => "Invalid message",
+            ErrorCode::Internal => "Server error",
+            ErrorCode::Forbidden => "Not allowed for this player",
+            ErrorCode::NoHintsLeft => "No hints left",
+            ErrorCode::Unknown => "Unknown error",
+        };
+        write!(f, "{}", description)
+    }
+}
+
+impl PackableField for ErrorCode {
+    fn pack_field(&self, packed: &mut Vec<u8>) -> Result<(), MessageError> {
+        packed.push(self.as_u8());
+        Ok(())
+    }
+
+    fn unpack_field(msg_bytes: &[u8], offset: &mut usize) -> Result<Self, MessageError> {
+        u8::unpack_field(msg_bytes, offset).map(ErrorCode::from_u8)
+    }
+}
+
+/// Rejection sent by the server, either right before it closes the connection or for a
+/// single message it ignored.
+#[derive(Default, Debug, Clone, Serialize, Deserialize, Packable)]
+#[serde(default)]
+pub struct ErrorMessage {
+    pub code: ErrorCode,
+    #[packable(with = codec::rest)]
+    pub message: String,
+}
+
+impl fmt::Display for ErrorMessage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.message.is_empty() {
+            return write!(f, "{}", self.code);
+        }
+        write!(f, "{}: {}", self.code, self.message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pack_unpack_error_message() {
+        let error_message = ErrorMessage {
+            code: ErrorCode::WrongRole,
+            message: String::from("Only asking players evaluate guesses"),
+        };
+
+        let bytes = error_message.pack().unwrap();
+        let mut check_instance = ErrorMessage::default();
+        check_instance
+            .unpack(&bytes)
+            .expect("Unpacking should not fail");
+
+        assert_eq!(check_instance.code, ErrorCode::WrongRole);
+        assert_eq!(check_instance.message, error_message.message);
+    }
+
+    #[test]
+    fn unknown_error_codes_are_kept_readable() {
+        let mut check_instance = ErrorMessage::default();
+        check_instance
+            .unpack(&[200, b'o', b'k'])
+            .expect("Unpacking should not fail");
+
+        assert_eq!(check_instance.code, ErrorCode::Unknown);
+        assert_eq!(check_instance.to_string(), "Unknown error: ok");
+    }
+}
@@ -1,46 +1,15 @@
-use crate::{traits::Packable, MessageError};
+use crate::codec;
+use crate::Packable;
+use serde::{Deserialize, Serialize};
 
-#[derive(Default)]
+#[derive(Default, Debug, Serialize, Deserialize, Packable)]
+#[serde(default)]
 pub struct Evaluation {
-    pub hint: Option<String>,
     pub guessed: bool,
-}
-
-impl Packable for Evaluation {
-    fn pack(&self) -> Vec<u8> {
-        let mut packed = Vec::new();
-        packed.push(self.guessed as u8);
-
-        if let Some(hint) = &self.hint {
-            let hint_bytes = hint.as_bytes();
-            packed.extend_from_slice(hint_bytes);
-        }
-
-        packed
-    }
-
-    fn unpack(&mut self, msg_bytes: &[u8]) -> Result<(), MessageError> {
-        if msg_bytes.is_empty() {
-            return Err(MessageError::BadUnpack(
-                "Invalid message format: data missing",
-            ));
-        }
-
-        self.guessed = msg_bytes[0] != 0;
-
-        let hint_slice = &msg_bytes[1..];
-
-        if hint_slice.is_empty() {
-            self.hint = None;
-            return Ok(());
-        }
-
-        let hint = String::from_utf8(hint_slice.to_vec())
-            .map_err(|_| MessageError::BadUnpack("Invalid hint bytes"))?;
-        self.hint = Some(hint);
-
-        Ok(())
-    }
+    pub hints_remaining: u8,
+    pub score: u16,
+    #[packable(with = codec::optional_rest)]
+    pub hint: Option<String>,
 }
 
 #[cfg(test)]
@@ -55,9 +24,11 @@ mod tests {
         let evaluation_instance = Evaluation {
             hint: hint_value.clone(),
             guessed: guessed_value,
+            hints_remaining: 2,
+            score: 340,
         };
 
-        let bytes = evaluation_instance.pack();
+        let bytes = evaluation_instance.pack().unwrap();
         let mut check_instance = Evaluation::default();
         check_instance
             .unpack(&bytes)
@@ -65,6 +36,8 @@ mod tests {
 
         assert_eq!(check_instance.hint, hint_value);
         assert_eq!(check_instance.guessed, guessed_value);
+        assert_eq!(check_instance.hints_remaining, 2);
+        assert_eq!(check_instance.score, 340);
     }
 
     #[test]
@@ -75,9 +48,10 @@ mod tests {
         let evaluation_instance = Evaluation {
             hint: hint_value,
             guessed: guessed_value,
+            ..Default::default()
         };
 
-        let bytes = evaluation_instance.pack();
+        let bytes = evaluation_instance.pack().unwrap();
         let mut check_instance = Evaluation::default();
         check_instance
             .unpack(&bytes)
@@ -86,4 +60,10 @@ mod tests {
         assert_eq!(check_instance.hint, None);
         assert_eq!(check_instance.guessed, guessed_value);
     }
+
+    #[test]
+    fn unpack_evaluation_with_missing_score() {
+        let mut check_instance = Evaluation::default();
+        assert!(check_instance.unpack(&[1, 0]).is_err());
+    }
 }
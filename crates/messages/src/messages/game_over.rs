@@ -0,0 +1,62 @@
+use crate::traits::PackableField;
+use crate::{MessageError, Packable, PlayerId};
+use serde::{Deserialize, Serialize};
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GameOverReason {
+    #[default]
+    MaxGuessesReached,
+}
+
+impl PackableField for GameOverReason {
+    fn pack_field(&self, packed: &mut Vec<u8>) -> Result<(), MessageError> {
+        packed.push(match self {
+            GameOverReason::MaxGuessesReached => 0,
+        });
+        Ok(())
+    }
+
+    fn unpack_field(msg_bytes: &[u8], offset: &mut usize) -> Result<Self, MessageError> {
+        match u8::unpack_field(msg_bytes, offset)? {
+            0 => Ok(GameOverReason::MaxGuessesReached),
+            _ => Err(MessageError::decode(
+                *offset - 1,
+                "Invalid game over reason",
+            )),
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize, Packable)]
+pub struct GameOver {
+    pub winner: PlayerId,
+    #[serde(default)]
+    pub reason: GameOverReason,
+    #[serde(default)]
+    pub answer: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pack_unpack_game_over() {
+        let game_over = GameOver {
+            winner: PlayerId::random(),
+            reason: GameOverReason::MaxGuessesReached,
+            answer: String::from("42"),
+        };
+
+        let bytes = game_over.pack().unwrap();
+        let mut check_instance = GameOver::default();
+        check_instance
+            .unpack(&bytes)
+            .expect("Unpacking should not fail");
+
+        assert_eq!(check_instance.winner, game_over.winner);
+        assert_eq!(check_instance.reason, GameOverReason::MaxGuessesReached);
+        assert_eq!(check_instance.answer, game_over.answer);
+    }
+}
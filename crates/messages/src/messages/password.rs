@@ -1,21 +1,19 @@
-use crate::{traits::Packable, MessageError};
+use crate::codec;
+use crate::Packable;
+use serde::{Deserialize, Serialize};
+use std::fmt;
 
-#[derive(Default)]
+#[derive(Default, Serialize, Deserialize, Packable)]
 pub struct Password {
+    #[packable(with = codec::rest)]
     pub password: String,
 }
 
-impl Packable for Password {
-    fn pack(&self) -> Vec<u8> {
-        let password_bytes = self.password.as_bytes().to_vec();
-        password_bytes
-    }
-
-    fn unpack(&mut self, msg_bytes: &[u8]) -> Result<(), MessageError> {
-        self.password = String::from_utf8(msg_bytes.to_vec())
-            .map_err(|_| MessageError::BadUnpack("Invalid UTF-8 sequence in password"))?;
-
-        Ok(())
+impl fmt::Debug for Password {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Password")
+            .field("password", &"***")
+            .finish()
     }
 }
 
@@ -29,7 +27,7 @@ mod tests {
             password: "12345".into(),
         };
 
-        let bytes = id_instance.pack();
+        let bytes = id_instance.pack().unwrap();
         let mut check_instance = Password::default();
         check_instance
             .unpack(&bytes)
@@ -1,52 +1,186 @@
 use crate::constants::ID_LENGTH;
-use crate::traits::Packable;
+use crate::helpers::get_random_id;
+use crate::traits::PackableField;
 use crate::MessageError;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
 
-#[derive(Default, Debug)]
-pub struct PlayerId {
-    pub player_id: String,
+/// Player id of exactly `ID_LENGTH` ASCII alphanumeric characters.
+///
+/// Ids are validated when parsed or unpacked, so every `PlayerId` can be packed into the
+/// fixed width id fields of the binary protocol. The default id is a placeholder of zeros
+/// for messages whose ids are filled in before sending.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PlayerId([u8; ID_LENGTH as usize]);
+
+impl PlayerId {
+    /// Id checked at compile time, e.g. for constants.
+    pub const fn from_static(id: &str) -> Self {
+        match Self::from_bytes(id.as_bytes()) {
+            Some(player_id) => player_id,
+            None => panic!("player ids are ID_LENGTH alphanumeric characters"),
+        }
+    }
+
+    const fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != ID_LENGTH as usize {
+            return None;
+        }
+
+        let mut id = [0; ID_LENGTH as usize];
+        let mut index = 0;
+        while index < id.len() {
+            if !bytes[index].is_ascii_alphanumeric() {
+                return None;
+            }
+            id[index] = bytes[index];
+            index += 1;
+        }
+
+        Some(PlayerId(id))
+    }
+
+    pub fn random() -> Self {
+        Self::from_bytes(get_random_id().as_bytes()).expect("Random ids are alphanumeric")
+    }
+
+    pub fn as_str(&self) -> &str {
+        std::str::from_utf8(&self.0).expect("Player ids are ASCII")
+    }
 }
 
-impl Packable for PlayerId {
-    fn pack(&self) -> Vec<u8> {
-        let mut packed = vec![];
-        packed.extend_from_slice(self.player_id.as_bytes());
+impl Default for PlayerId {
+    fn default() -> Self {
+        PlayerId([b'0'; ID_LENGTH as usize])
+    }
+}
+
+impl FromStr for PlayerId {
+    type Err = MessageError;
 
-        packed
+    fn from_str(id: &str) -> Result<Self, Self::Err> {
+        Self::from_bytes(id.as_bytes()).ok_or_else(|| MessageError::InvalidPlayerId(id.into()))
     }
-    fn unpack(&mut self, msg_bytes: &[u8]) -> Result<(), MessageError> {
-        let receiver_id_bytes =
+}
+
+impl TryFrom<&str> for PlayerId {
+    type Error = MessageError;
+
+    fn try_from(id: &str) -> Result<Self, Self::Error> {
+        id.parse()
+    }
+}
+
+impl PartialEq<str> for PlayerId {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for PlayerId {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl fmt::Display for PlayerId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Debug for PlayerId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("PlayerId").field(&self.as_str()).finish()
+    }
+}
+
+impl Serialize for PlayerId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for PlayerId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let id = String::deserialize(deserializer)?;
+        id.parse().map_err(de::Error::custom)
+    }
+}
+
+/// Packed as its `ID_LENGTH` bytes, without a length.
+impl PackableField for PlayerId {
+    fn pack_field(&self, packed: &mut Vec<u8>) -> Result<(), MessageError> {
+        packed.extend_from_slice(&self.0);
+        Ok(())
+    }
+
+    fn unpack_field(msg_bytes: &[u8], offset: &mut usize) -> Result<Self, MessageError> {
+        let start = *offset;
+        let bytes =
             msg_bytes
-                .get(0..ID_LENGTH as usize)
-                .ok_or(MessageError::BadUnpack(
-                    "Invalid message format: Receiver ID missing",
+                .get(start..start + ID_LENGTH as usize)
+                .ok_or(MessageError::decode(
+                    start,
+                    "Invalid message format: player id missing",
                 ))?;
-        self.player_id = String::from_utf8(receiver_id_bytes.to_vec())
-            .map_err(|_| MessageError::BadUnpack("Invalid UTF-8 sequence in receiver ID"))?;
+        let player_id =
+            Self::from_bytes(bytes).ok_or(MessageError::decode(start, "Invalid player id"))?;
+        *offset += ID_LENGTH as usize;
 
-        Ok(())
+        Ok(player_id)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::get_random_id;
-
     use super::*;
 
     #[test]
     fn pack_unpack_player_id() {
-        let player_id = get_random_id();
-        let player_id_instance = PlayerId {
-            player_id: player_id.clone(),
-        };
-
-        let bytes = player_id_instance.pack();
-        let mut check_instance = PlayerId::default();
-        check_instance
-            .unpack(&bytes)
-            .expect("Unpacking should not fail");
-
-        assert_eq!(player_id, check_instance.player_id);
+        let player_id = PlayerId::random();
+
+        let mut bytes = Vec::new();
+        player_id.pack_field(&mut bytes).unwrap();
+        let mut offset = 0;
+        let check_instance =
+            PlayerId::unpack_field(&bytes, &mut offset).expect("Unpacking should not fail");
+
+        assert_eq!(player_id, check_instance);
+        assert_eq!(offset, ID_LENGTH as usize);
+        assert_eq!(bytes, player_id.as_str().as_bytes());
+    }
+
+    #[test]
+    fn only_alphanumeric_ids_of_id_length_parse() {
+        assert_eq!("player0001".parse::<PlayerId>().unwrap(), "player0001");
+        for id in ["short", "player00011", "player 001", "plåyer001", ""] {
+            assert!(matches!(
+                id.parse::<PlayerId>(),
+                Err(MessageError::InvalidPlayerId(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn invalid_ids_are_decode_errors() {
+        let mut offset = 0;
+        assert!(matches!(
+            PlayerId::unpack_field(b"xxplayer-001", &mut 2),
+            Err(MessageError::Decode { offset: 2, .. })
+        ));
+        assert!(matches!(
+            PlayerId::unpack_field(b"player", &mut offset),
+            Err(MessageError::Decode { offset: 0, .. })
+        ));
+        assert_eq!(offset, 0);
+    }
+
+    #[test]
+    fn ids_are_json_strings() {
+        let player_id: PlayerId = serde_json::from_str("\"player0001\"").unwrap();
+        assert_eq!(serde_json::to_string(&player_id).unwrap(), "\"player0001\"");
+        assert!(serde_json::from_str::<PlayerId>("\"player 001\"").is_err());
     }
 }
@@ -0,0 +1,81 @@
+use crate::codec;
+use crate::traits::PackableField;
+use crate::{MessageError, Packable, Role};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Packable)]
+pub struct PlayerRole {
+    pub role: Role,
+    #[serde(default)]
+    #[packable(with = codec::trailing)]
+    pub categories: Vec<String>,
+}
+
+impl Default for PlayerRole {
+    fn default() -> Self {
+        PlayerRole {
+            role: Role::GuessingPlayer,
+            categories: Vec::new(),
+        }
+    }
+}
+
+impl From<Role> for PlayerRole {
+    fn from(role: Role) -> Self {
+        PlayerRole {
+            role,
+            categories: Vec::new(),
+        }
+    }
+}
+
+/// Any role byte other than 0 is read as asking, as older clients did.
+impl PackableField for Role {
+    fn pack_field(&self, packed: &mut Vec<u8>) -> Result<(), MessageError> {
+        packed.push(match self {
+            Role::GuessingPlayer => 0,
+            Role::AskingPlayer => 1,
+        });
+        Ok(())
+    }
+
+    fn unpack_field(msg_bytes: &[u8], offset: &mut usize) -> Result<Self, MessageError> {
+        match u8::unpack_field(msg_bytes, offset)? {
+            0 => Ok(Role::GuessingPlayer),
+            _ => Ok(Role::AskingPlayer),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pack_unpack_player_role_with_categories() {
+        let player_role = PlayerRole {
+            role: Role::GuessingPlayer,
+            categories: vec![String::from("nature"), String::from("food")],
+        };
+
+        let bytes = player_role.pack().unwrap();
+        let mut check_instance = PlayerRole::default();
+        check_instance
+            .unpack(&bytes)
+            .expect("Unpacking should not fail");
+
+        assert_eq!(check_instance.role, Role::GuessingPlayer);
+        assert_eq!(check_instance.categories, player_role.categories);
+    }
+
+    #[test]
+    fn unpack_player_role_without_categories() {
+        let mut check_instance = PlayerRole::default();
+        check_instance
+            .unpack(&[1])
+            .expect("Unpacking should not fail");
+
+        assert_eq!(check_instance.role, Role::AskingPlayer);
+        assert!(check_instance.categories.is_empty());
+    }
+}
@@ -1,118 +1,87 @@
-use crate::constants::ID_LENGTH;
-use crate::helpers::get_string_slice_length;
-use crate::traits::Packable;
-use crate::MessageError;
-
-#[derive(Default, Clone)]
-pub struct Riddle {
-    pub sender: String,
-    pub asking_player: String,
-    pub guessing_player: String,
-    pub message: String,
-    pub hint: Option<String>,
+use crate::traits::PackableField;
+use crate::{MessageError, Packable, PlayerId};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Medium,
+    Hard,
 }
 
-impl Packable for Riddle {
-    fn pack(&self) -> Vec<u8> {
-        let question_length = get_string_slice_length(&self.message);
-        let hint_length = self.hint.as_ref().map_or(0, |h| get_string_slice_length(h));
-        let mut packed = vec![];
-
-        packed.extend_from_slice(self.sender.as_bytes());
-        packed.extend_from_slice(self.asking_player.as_bytes());
-        packed.extend_from_slice(self.guessing_player.as_bytes());
-        packed.push(question_length);
-        packed.extend_from_slice(self.message.as_bytes());
-        packed.push(hint_length);
-        packed.extend_from_slice(self.hint.as_ref().map_or(&[], |h| h.as_bytes()));
-
-        packed
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Easy => write!(f, "easy"),
+            Self::Medium => write!(f, "medium"),
+            Self::Hard => write!(f, "hard"),
+        }
     }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
 
-    fn unpack(&mut self, msg_bytes: &[u8]) -> Result<(), MessageError> {
-        let mut offset = 0;
-        let id_end = offset + ID_LENGTH as usize;
-        let sender_bytes = msg_bytes
-            .get(offset..id_end)
-            .ok_or(MessageError::BadUnpack(
-                "Invalid message format: sending player id missing",
-            ))?;
-        self.sender = String::from_utf8(sender_bytes.to_vec())
-            .map_err(|_| MessageError::BadUnpack("Invalid UTF-8 sequence in sender id"))?;
-
-        offset = id_end;
-        let id_end = offset + ID_LENGTH as usize;
-        let asking_player_bytes = msg_bytes
-            .get(offset..id_end)
-            .ok_or(MessageError::BadUnpack(
-                "Invalid message format: asking player ID missing",
-            ))?;
-        self.asking_player = String::from_utf8(asking_player_bytes.to_vec()).map_err(|_| {
-            MessageError::BadUnpack("Invalid UTF-8 sequence in from asking player id")
-        })?;
-
-        offset = id_end;
-        let id_end = offset + ID_LENGTH as usize;
-        let guessing_player_bytes =
-            msg_bytes
-                .get(offset..id_end)
-                .ok_or(MessageError::BadUnpack(
-                    "Invalid message format: guessing player id missing",
-                ))?;
-        self.guessing_player = String::from_utf8(guessing_player_bytes.to_vec()).map_err(|_| {
-            MessageError::BadUnpack("Invalid UTF-8 sequence in from guessing player id")
-        })?;
-
-        offset = id_end;
-        let message_length = *msg_bytes.get(offset).ok_or(MessageError::BadUnpack(
-            "Invalid message format: Message length missing",
-        ))? as usize;
-        offset += 1;
-
-        let message_end = offset + message_length;
-        let message_bytes = msg_bytes
-            .get(offset..message_end)
-            .ok_or(MessageError::BadUnpack(
-                "Invalid message format: Message missing",
-            ))?;
-        self.message = String::from_utf8(message_bytes.to_vec())
-            .map_err(|_| MessageError::BadUnpack("Invalid UTF-8 sequence in message"))?;
-        offset = message_end;
-
-        let hint_length = *msg_bytes.get(offset).ok_or(MessageError::BadUnpack(
-            "Invalid message format: Hint length missing",
-        ))? as usize;
-        offset += 1;
-
-        if hint_length > 0 {
-            let hint_end = offset + hint_length;
-            let hint_bytes = msg_bytes
-                .get(offset..hint_end)
-                .ok_or(MessageError::BadUnpack(
-                    "Invalid message format: Hint missing",
-                ))?;
-            self.hint = Some(
-                String::from_utf8(hint_bytes.to_vec())
-                    .map_err(|_| MessageError::BadUnpack("Invalid UTF-8 sequence in hint"))?,
-            );
-        } else {
-            self.hint = None;
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "easy" | "e" => Ok(Self::Easy),
+            "medium" | "m" => Ok(Self::Medium),
+            "hard" | "h" => Ok(Self::Hard),
+            _ => Err(format!("Unknown difficulty {}", value)),
         }
+    }
+}
 
+impl PackableField for Difficulty {
+    fn pack_field(&self, packed: &mut Vec<u8>) -> Result<(), MessageError> {
+        packed.push(match self {
+            Difficulty::Easy => 0,
+            Difficulty::Medium => 1,
+            Difficulty::Hard => 2,
+        });
         Ok(())
     }
+
+    fn unpack_field(msg_bytes: &[u8], offset: &mut usize) -> Result<Self, MessageError> {
+        match u8::unpack_field(msg_bytes, offset)? {
+            0 => Ok(Difficulty::Easy),
+            1 => Ok(Difficulty::Medium),
+            2 => Ok(Difficulty::Hard),
+            _ => Err(MessageError::decode(*offset - 1, "Invalid difficulty")),
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize, Packable)]
+pub struct Riddle {
+    pub sender: PlayerId,
+    pub asking_player: PlayerId,
+    pub guessing_player: PlayerId,
+    #[serde(default)]
+    pub message: String,
+    #[serde(default)]
+    pub hint: Option<String>,
+    #[serde(default)]
+    pub max_guesses: u8,
+    #[serde(default)]
+    pub category: String,
+    #[serde(default)]
+    pub difficulty: Difficulty,
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::helpers::get_random_id;
 
     #[test]
     fn pack_unpack_riddle() {
-        let sender = get_random_id();
-        let asking_player = get_random_id();
-        let guessing_player = get_random_id();
+        let sender = PlayerId::random();
+        let asking_player = PlayerId::random();
+        let guessing_player = PlayerId::random();
         let message = String::from("What is meaning of life?");
         let hint = String::from("Galaxy");
         let riddle_instance = Riddle {
@@ -121,9 +90,12 @@ mod test {
             guessing_player,
             message,
             hint: Some(hint.clone()),
+            max_guesses: 5,
+            category: String::from("philosophy"),
+            difficulty: Difficulty::Hard,
         };
 
-        let bytes = riddle_instance.pack();
+        let bytes = riddle_instance.pack().unwrap();
         let mut check_instance = Riddle::default();
         check_instance
             .unpack(&bytes)
@@ -137,13 +109,16 @@ mod test {
         );
         assert_eq!(check_instance.message, riddle_instance.message);
         assert_eq!(check_instance.hint, Some(hint));
+        assert_eq!(check_instance.max_guesses, 5);
+        assert_eq!(check_instance.category, "philosophy");
+        assert_eq!(check_instance.difficulty, Difficulty::Hard);
     }
 
     #[test]
     fn pack_unpack_riddle_with_no_hint() {
-        let sender = get_random_id();
-        let asking_player = get_random_id();
-        let guessing_player = get_random_id();
+        let sender = PlayerId::random();
+        let asking_player = PlayerId::random();
+        let guessing_player = PlayerId::random();
         let message = String::from("What is meaning of life?");
 
         let riddle_instance = Riddle {
@@ -152,9 +127,11 @@ mod test {
             guessing_player,
             message,
             hint: None,
+            max_guesses: 0,
+            ..Default::default()
         };
 
-        let bytes = riddle_instance.pack();
+        let bytes = riddle_instance.pack().unwrap();
 
         let mut check_instance = Riddle::default();
 
@@ -171,4 +148,11 @@ mod test {
         assert_eq!(check_instance.message, riddle_instance.message);
         assert_eq!(check_instance.hint, None);
     }
+
+    #[test]
+    fn parse_difficulty() {
+        assert_eq!("Easy".parse(), Ok(Difficulty::Easy));
+        assert_eq!(" hard ".parse(), Ok(Difficulty::Hard));
+        assert!("impossible".parse::<Difficulty>().is_err());
+    }
 }
@@ -1,12 +1,54 @@
-use crate::{Message, MessageError};
+use crate::{Frame, Message, MessageError};
 
 pub trait Packable {
-    fn pack(&self) -> Vec<u8>;
+    fn pack(&self) -> Result<Vec<u8>, MessageError>;
     fn unpack(&mut self, msg_bytes: &[u8]) -> Result<(), MessageError>;
 }
 
+/// Value inside a message body, packed by `#[derive(Packable)]` after the fields before it.
+///
+/// `pack_field` fails on values the format can not carry, `unpack_field` starts at `offset`
+/// and moves it past the bytes it used.
+pub trait PackableField: Sized {
+    fn pack_field(&self, packed: &mut Vec<u8>) -> Result<(), MessageError>;
+    fn unpack_field(msg_bytes: &[u8], offset: &mut usize) -> Result<Self, MessageError>;
+}
+
 pub trait Streamable: Send {
     fn read(&mut self) -> Result<Message, MessageError>;
     fn write(&mut self, message: &Message) -> Result<(), MessageError>;
-    fn shutdown(&mut self) -> Result<(), &'static str>;
+    fn shutdown(&mut self) -> Result<(), MessageError>;
+
+    /// Reads a message together with its request id, streams without request ids
+    /// always return `None`.
+    fn read_frame(&mut self) -> Result<Frame, MessageError> {
+        self.read().map(Frame::from)
+    }
+
+    /// Writes a message with its request id, streams without request ids drop it.
+    fn write_frame(&mut self, frame: &Frame) -> Result<(), MessageError> {
+        self.write(&frame.message)
+    }
+}
+
+impl<S: Streamable + ?Sized> Streamable for Box<S> {
+    fn read(&mut self) -> Result<Message, MessageError> {
+        (**self).read()
+    }
+
+    fn write(&mut self, message: &Message) -> Result<(), MessageError> {
+        (**self).write(message)
+    }
+
+    fn shutdown(&mut self) -> Result<(), MessageError> {
+        (**self).shutdown()
+    }
+
+    fn read_frame(&mut self) -> Result<Frame, MessageError> {
+        (**self).read_frame()
+    }
+
+    fn write_frame(&mut self, frame: &Frame) -> Result<(), MessageError> {
+        (**self).write_frame(frame)
+    }
 }
@@ -0,0 +1,250 @@
+use guessing_game::logging::{log_error, log_info, log_warn};
+use guessing_game::messages::{
+    load_recording, Direction, Frame, Message, MessageError, PlayerId, RecordedMessage, Streamable,
+    TcpMessageStream, UnixMessageStream,
+};
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::net::{TcpListener, TcpStream};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::time::{Duration, Instant};
+use std::{env, process, thread};
+
+const USAGE: &str = "Usage: replay <recording> <server|client> [--connection=tcp|unix] \
+[--address=<address>] [--send=sent|received] [--password=<password>] [--no-delay]";
+const DRAIN_TIMEOUT: Duration = Duration::from_millis(500);
+
+enum Target {
+    Server,
+    Client,
+}
+
+struct Options {
+    recording: String,
+    target: Target,
+    unix_socket: bool,
+    address: Option<String>,
+    send: Direction,
+    /// Sent in place of the blanked password of recorded logins.
+    password: Option<String>,
+    delay: bool,
+}
+
+fn get_options() -> Result<Options, String> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let positional: Vec<&String> = args.iter().filter(|arg| !arg.starts_with("--")).collect();
+    let [recording, target] = positional[..] else {
+        return Err(USAGE.into());
+    };
+
+    let target = match target.as_str() {
+        "server" => Target::Server,
+        "client" => Target::Client,
+        _ => return Err(USAGE.into()),
+    };
+    let flag = |prefix: &str| args.iter().find_map(|arg| arg.strip_prefix(prefix));
+    let send = match (flag("--send="), &target) {
+        (Some("sent"), _) | (None, Target::Server) => Direction::Sent,
+        (Some("received"), _) | (None, Target::Client) => Direction::Received,
+        _ => return Err(USAGE.into()),
+    };
+
+    Ok(Options {
+        recording: recording.clone(),
+        target,
+        unix_socket: flag("--connection=") == Some("unix"),
+        address: flag("--address=").map(str::to_owned),
+        send,
+        password: flag("--password=").map(str::to_owned),
+        delay: !args.iter().any(|arg| arg == "--no-delay"),
+    })
+}
+
+/// Logs everything the other side sent, returning the last player id it handed out.
+fn drain(stream: &mut impl Streamable) -> Result<Option<PlayerId>, String> {
+    let mut player_id = None;
+    loop {
+        match stream.read() {
+            Ok(message) => {
+                log_info(format!("received {:?}", message));
+                if let Message::PlayerId(id) = message {
+                    player_id = Some(id);
+                }
+            }
+            Err(MessageError::EmptyRead) => return Ok(player_id),
+            Err(err) => return Err(err.to_string()),
+        }
+    }
+}
+
+/// Player id the other side handed out at the recorded login.
+fn recorded_login_id(recorded: &[RecordedMessage], send: Direction) -> Option<PlayerId> {
+    recorded
+        .iter()
+        .filter(|recorded_message| recorded_message.direction != send)
+        .find_map(|recorded_message| match recorded_message.message {
+            Message::PlayerId(player_id) => Some(player_id),
+            _ => None,
+        })
+}
+
+/// Replaces `from` with `to` in every player id field of `message`.
+fn replace_player_id(message: &mut Message, from: PlayerId, to: PlayerId) {
+    let player_ids = match message {
+        Message::Riddle(riddle) => vec![
+            &mut riddle.sender,
+            &mut riddle.asking_player,
+            &mut riddle.guessing_player,
+        ],
+        Message::OpponentSelected(opponent_selected) => vec![
+            &mut opponent_selected.guessing_player,
+            &mut opponent_selected.asking_player,
+        ],
+        Message::ChatMessage(chat_message) => vec![&mut chat_message.sender],
+        Message::GameOver(game_over) => vec![&mut game_over.winner],
+        Message::PlayerList(player_list) => player_list.opponent_ids.iter_mut().collect(),
+        Message::PlayerNotAvailable(player_id) | Message::PlayerId(player_id) => vec![player_id],
+        _ => Vec::new(),
+    };
+
+    player_ids
+        .into_iter()
+        .filter(|player_id| **player_id == from)
+        .for_each(|player_id| *player_id = to);
+}
+
+/// Sends recorded messages of one direction with their request ids, keeping the original
+/// gaps between them, and logs everything the other side sends back.
+///
+/// A server hands out a new player id at login, so recorded messages naming the recorded
+/// id are sent with the new one.
+fn replay(
+    mut stream: impl Streamable,
+    recorded: Vec<RecordedMessage>,
+    send: Direction,
+    delay: bool,
+) -> Result<(), String> {
+    let recorded_id = recorded_login_id(&recorded, send);
+    let mut live_id = None;
+    let started = Instant::now();
+
+    for mut recorded_message in recorded
+        .into_iter()
+        .filter(|recorded| recorded.direction == send)
+    {
+        while delay && started.elapsed() < recorded_message.elapsed {
+            live_id = drain(&mut stream)?.or(live_id);
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        if let (Some(recorded_id), Some(live_id)) = (recorded_id, live_id) {
+            replace_player_id(&mut recorded_message.message, recorded_id, live_id);
+        }
+        let is_login = matches!(recorded_message.message, Message::Password(_));
+
+        log_info(format!("sending {:?}", recorded_message.message));
+        stream
+            .write_frame(&Frame {
+                request_id: recorded_message.request_id,
+                message: recorded_message.message,
+            })
+            .map_err(|e| e.to_string())?;
+        live_id = drain(&mut stream)?.or(live_id);
+
+        // messages after the login need the new id, so it is awaited even without delays
+        let sent = Instant::now();
+        while is_login && recorded_id.is_some() && live_id.is_none() {
+            if sent.elapsed() >= DRAIN_TIMEOUT {
+                return Err("Login was not answered with a player id".into());
+            }
+            thread::sleep(Duration::from_millis(1));
+            live_id = drain(&mut stream)?;
+        }
+    }
+
+    let finished = Instant::now();
+    while finished.elapsed() < DRAIN_TIMEOUT {
+        if drain(&mut stream).is_err() {
+            log_info("Connection closed by the other side");
+            return Ok(());
+        }
+        thread::sleep(Duration::from_millis(1));
+    }
+
+    stream.shutdown().map_err(|e| e.to_string())
+}
+
+fn run(options: Options) -> Result<(), String> {
+    let file = File::open(&options.recording).map_err(|e| e.to_string())?;
+    let mut recorded = load_recording(BufReader::new(file)).map_err(|e| e.to_string())?;
+    log_info(format!("Loaded {} recorded messages", recorded.len()));
+    if let Some(password) = options.password.as_ref() {
+        for recorded_message in recorded.iter_mut() {
+            if let Message::Password(login) = &mut recorded_message.message {
+                login.password = password.clone();
+            }
+        }
+    }
+
+    match (options.target, options.unix_socket) {
+        (Target::Server, false) => {
+            let address = options.address.as_deref().unwrap_or("localhost:9000");
+            let stream = TcpStream::connect(address).map_err(|e| e.to_string())?;
+            stream.set_nonblocking(true).map_err(|e| e.to_string())?;
+            replay(
+                TcpMessageStream::from(stream),
+                recorded,
+                options.send,
+                options.delay,
+            )
+        }
+        (Target::Server, true) => {
+            let address = options.address.as_deref().unwrap_or("/tmp/guessing_game");
+            let stream = UnixStream::connect(address).map_err(|e| e.to_string())?;
+            stream.set_nonblocking(true).map_err(|e| e.to_string())?;
+            replay(
+                UnixMessageStream::from(stream),
+                recorded,
+                options.send,
+                options.delay,
+            )
+        }
+        (Target::Client, false) => {
+            let address = options.address.as_deref().unwrap_or("localhost:9000");
+            let listener = TcpListener::bind(address).map_err(|e| e.to_string())?;
+            log_info(format!("Waiting for client on {}", address));
+            let (stream, _) = listener.accept().map_err(|e| e.to_string())?;
+            stream.set_nonblocking(true).map_err(|e| e.to_string())?;
+            replay(
+                TcpMessageStream::from(stream),
+                recorded,
+                options.send,
+                options.delay,
+            )
+        }
+        (Target::Client, true) => {
+            let address = options.address.as_deref().unwrap_or("/tmp/guessing_game");
+            if fs::metadata(address).is_ok() {
+                log_warn(format!("Removing existing socket {}", address));
+                fs::remove_file(address).map_err(|e| e.to_string())?;
+            }
+            let listener = UnixListener::bind(address).map_err(|e| e.to_string())?;
+            log_info(format!("Waiting for client on {}", address));
+            let (stream, _) = listener.accept().map_err(|e| e.to_string())?;
+            stream.set_nonblocking(true).map_err(|e| e.to_string())?;
+            replay(
+                UnixMessageStream::from(stream),
+                recorded,
+                options.send,
+                options.delay,
+            )
+        }
+    }
+}
+
+fn main() {
+    if let Err(err) = get_options().and_then(run) {
+        log_error(err);
+        process::exit(1);
+    }
+}
@@ -1,41 +1,53 @@
+mod chat;
 mod evaluation;
+mod hint;
+mod house;
 mod login;
 mod opponent_selected;
 mod player_list;
+mod rate_limit;
 mod riddle;
 
+use crate::config::Config;
 use crate::player::Player;
+use chat::handle_chat_message;
 use evaluation::handle_evaluation;
-use guessing_game::logging::{log_error, log_warn};
+use guessing_game::logging::{log_error, log_info, log_warn};
 use guessing_game::messages::{
-    ConnectionType, Message, MessageError, PlayerList, Role, Streamable,
+    ConnectionType, ErrorCode, ErrorMessage, Frame, Message, MessageError, PlayerId, PlayerList,
+    Role, Streamable,
 };
+use hint::handle_request_hint;
+use house::handle_request_house_riddle;
 use login::handle_login;
 use opponent_selected::handle_opponent_selected;
+use rate_limit::{RateLimit, RateLimiter};
 use riddle::handle_riddle;
+use std::net::IpAddr;
 use std::sync::{Arc, Mutex};
 use std::{collections::HashMap, thread, time};
 
 pub struct ConnectionError {
     pub err: String,
-    pub player_id: Option<String>,
+    /// Sent to the client with `err` before the connection is closed.
+    pub code: ErrorCode,
+    pub player_id: Option<PlayerId>,
 }
 
-pub fn remove_player(players: Arc<Mutex<HashMap<String, Player>>>, player_id: &str) {
+pub fn remove_player(players: Arc<Mutex<HashMap<PlayerId, Player>>>, player_id: &PlayerId) {
     let mut players_lock = players.lock().unwrap();
     match players_lock.remove(player_id) {
         Some(removed_player) => {
             if let Some(opponent) = removed_player.opponent {
                 let (opponent_id, _) = opponent;
                 if let Some(opponent) = players_lock.get_mut(&opponent_id) {
-                    opponent.opponent = None;
-                    opponent.question = None;
+                    opponent.leave_game();
 
                     if let Err(error) = opponent
                         .stream
                         .lock()
                         .unwrap()
-                        .write(&Message::PlayerNotAvailable(player_id.to_owned()))
+                        .write(&Message::PlayerNotAvailable(*player_id))
                     {
                         log_error(error);
                     }
@@ -49,13 +61,10 @@ pub fn remove_player(players: Arc<Mutex<HashMap<String, Player>>>, player_id: &s
                     if opponent_id != player_id {
                         return;
                     }
-                    player.opponent = None;
-                    player.question = None;
+                    player.leave_game();
                     let mut stream_lock = player.stream.lock().unwrap();
 
-                    if let Err(err) =
-                        stream_lock.write(&Message::PlayerNotAvailable(player_id.to_owned()))
-                    {
+                    if let Err(err) = stream_lock.write(&Message::PlayerNotAvailable(*player_id)) {
                         log_error(err);
                     }
                 }
@@ -63,123 +72,270 @@ pub fn remove_player(players: Arc<Mutex<HashMap<String, Player>>>, player_id: &s
         }
     }
 }
+
+/// Rejects messages claiming to come from another player than the one logged in.
+fn check_player(claimed: &PlayerId, player_id: &PlayerId) -> Result<(), ConnectionError> {
+    if claimed == player_id {
+        return Ok(());
+    }
+
+    log_warn(format!(
+        "Player {} sent a message as {}",
+        player_id, claimed
+    ));
+    Err(ConnectionError {
+        err: format!("Message names player {} instead of {}", claimed, player_id),
+        code: ErrorCode::Forbidden,
+        player_id: Some(*player_id),
+    })
+}
+
+/// Sends the reason of a rejection to the client, answering `request_id` if it has one.
+pub fn send_error(
+    stream: &Arc<Mutex<dyn Streamable>>,
+    code: ErrorCode,
+    message: &str,
+    request_id: Option<u32>,
+) {
+    let error_message = ErrorMessage {
+        code,
+        message: message.to_owned(),
+    };
+    let mut stream_lock = stream.lock().unwrap();
+    if let Err(err) = stream_lock.write_frame(&Frame {
+        request_id,
+        message: Message::Error(error_message),
+    }) {
+        log_error(err);
+    }
+}
+
+/// Tells the client why it is disconnected, then closes the connection.
+fn reject_client(
+    stream: &Arc<Mutex<dyn Streamable>>,
+    players: &Arc<Mutex<HashMap<PlayerId, Player>>>,
+    err: &ConnectionError,
+    player_id: &Option<PlayerId>,
+    request_id: Option<u32>,
+) {
+    send_error(stream, err.code, &err.err, request_id);
+    if let Err(e) = stream.lock().unwrap().shutdown() {
+        log_error(e);
+    }
+
+    if let Some(player_id) = player_id.as_ref() {
+        remove_player(players.clone(), player_id);
+    }
+}
+
+fn handle_message(
+    stream: &Arc<Mutex<dyn Streamable>>,
+    players: &Arc<Mutex<HashMap<PlayerId, Player>>>,
+    message: Message,
+    request_id: Option<u32>,
+    player_id: &mut Option<PlayerId>,
+    config: &Config,
+    peer: Option<IpAddr>,
+) -> Result<(), ConnectionError> {
+    match message {
+        Message::Password(password) => {
+            *player_id = handle_login(
+                stream.clone(),
+                players.clone(),
+                password,
+                config,
+                peer,
+                request_id,
+            )?;
+        }
+        Message::Riddle(riddle) => {
+            handle_riddle(stream.clone(), players.clone(), riddle, player_id, config)?;
+        }
+        Message::Evaluation(evaluation) => {
+            handle_evaluation(players.clone(), evaluation, player_id, config)?
+        }
+
+        Message::RequestGuessingPlayers(category) => {
+            let mut stream_lock = stream.lock().unwrap();
+            let players = players.lock().unwrap();
+            let free_guessing_player_ids = players
+                .values()
+                .filter_map(|player| {
+                    if player.opponent.is_none()
+                        && player
+                            .role
+                            .as_ref()
+                            .is_some_and(|role| role == &Role::GuessingPlayer)
+                        && category.as_ref().is_none_or(|category| {
+                            player.preferred_categories.is_empty()
+                                || player
+                                    .preferred_categories
+                                    .iter()
+                                    .any(|preferred| preferred.eq_ignore_ascii_case(category))
+                        })
+                    {
+                        return Some(player.id);
+                    }
+
+                    None
+                })
+                // a player list holds at most 255 ids
+                .take(u8::MAX as usize)
+                .collect::<Vec<PlayerId>>();
+            let player_list = PlayerList {
+                opponent_ids: free_guessing_player_ids,
+            };
+            stream_lock
+                .write_frame(&Frame {
+                    request_id,
+                    message: Message::PlayerList(player_list),
+                })
+                .map_err(|_| ConnectionError {
+                    err: "Can not send player list".into(),
+                    code: ErrorCode::Internal,
+                    player_id: *player_id,
+                })?;
+        }
+
+        Message::OpponentSelected(opponent_selected) => handle_opponent_selected(
+            stream.clone(),
+            players.clone(),
+            opponent_selected,
+            player_id,
+        )?,
+        Message::PlayerId(_)
+        | Message::PlayerList(_)
+        | Message::PlayerNotAvailable(_)
+        | Message::GameStart
+        | Message::GameOver(_)
+        | Message::Error(_) => {
+            return Err(ConnectionError {
+                err: "Message is only sent by the server".into(),
+                code: ErrorCode::Forbidden,
+                player_id: *player_id,
+            });
+        }
+        Message::RequestHouseRiddle => {
+            handle_request_house_riddle(stream.clone(), players.clone(), player_id, config)?
+        }
+        Message::RequestHint => {
+            handle_request_hint(stream.clone(), players.clone(), player_id, config)?
+        }
+        Message::ChatMessage(chat_message) => {
+            handle_chat_message(players.clone(), chat_message, player_id)?
+        }
+        Message::Unknown => {}
+        Message::RegisterPlayerRole(player_role) => {
+            let mut players_lock = players.lock().unwrap();
+            let Some(player_id) = player_id.as_ref() else {
+                return Err(ConnectionError {
+                    err: "Unauthorized action".into(),
+                    code: ErrorCode::NotLoggedIn,
+                    player_id: None,
+                });
+            };
+
+            match players_lock.get_mut(player_id) {
+                Some(player) => {
+                    player.role = Some(player_role.role);
+                    player.preferred_categories = player_role.categories;
+                }
+                None => {
+                    log_warn(format!("Unwaranted id {}", player_id));
+                    return Err(ConnectionError {
+                        err: "Unauthorized action".into(),
+                        code: ErrorCode::NotLoggedIn,
+                        player_id: Some(*player_id),
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Serves one connection, failed logins are throttled per `peer` address when it has one.
 pub fn handle_game_client(
     stream: impl Streamable + 'static,
-    players: Arc<Mutex<HashMap<String, Player>>>,
-    password_secret: &str,
+    players: Arc<Mutex<HashMap<PlayerId, Player>>>,
+    config: &Config,
+    peer: Option<IpAddr>,
 ) -> Result<(), ConnectionError> {
     let stream: Arc<Mutex<dyn Streamable>> = Arc::new(Mutex::new(stream));
     let mut player_id = None;
+    let mut rate_limiter = RateLimiter::default();
 
     loop {
         let mut stream_lock = stream.lock().unwrap();
-        let read_result = stream_lock.read();
+        let read_result = stream_lock.read_frame();
         drop(stream_lock);
         match read_result {
-            Ok(msg) => match msg {
-                Message::Password(password) => {
-                    player_id =
-                        handle_login(stream.clone(), players.clone(), password, password_secret)?;
-                }
-                Message::Riddle(riddle) => {
-                    handle_riddle(stream.clone(), players.clone(), riddle, &player_id)?;
-                }
-                Message::Evaluation(evaluation) => {
-                    handle_evaluation(players.clone(), evaluation, &player_id)?
-                }
-
-                Message::RequestGuessingPlayers => {
-                    let mut stream_lock = stream.lock().unwrap();
-                    let players = players.lock().unwrap();
-                    let free_guessing_player_ids = players
-                        .values()
-                        .filter_map(|player| {
-                            if player.opponent.is_none()
-                                && player
-                                    .role
-                                    .as_ref()
-                                    .is_some_and(|role| role == &Role::GuessingPlayer)
-                            {
-                                return Some(player.id.clone());
-                            }
-
-                            None
-                        })
-                        .collect::<Vec<String>>();
-                    let player_list = PlayerList {
-                        opponent_ids: free_guessing_player_ids,
-                    };
-                    stream_lock
-                        .write(&Message::PlayerList(player_list))
-                        .map_err(|_| ConnectionError {
-                            err: "Can not send player list".into(),
-                            player_id: player_id.clone(),
-                        })?;
-                }
-
-                Message::OpponentSelected(opponent_selected) => handle_opponent_selected(
-                    stream.clone(),
-                    players.clone(),
-                    opponent_selected,
-                    &player_id,
-                )?,
-                Message::PlayerId(_) => {}
-                Message::PlayerList(_) => return Ok(()),
-                Message::PlayerNotAvailable(player_id) => {
-                    remove_player(players.clone(), &player_id);
-                    return Ok(());
-                }
-                Message::GameStart => {}
-                Message::Unknown => {}
-                Message::RegisterPlayerRole(role) => {
-                    let mut players_lock = players.lock().unwrap();
-                    if player_id.is_none() {
-                        return Err(ConnectionError {
-                            err: "Unauthorized action".into(),
+            // responses echo the request id, see `Frame`
+            Ok(Frame {
+                request_id,
+                message,
+            }) => {
+                match rate_limiter.check(&message) {
+                    RateLimit::Allowed => {}
+                    RateLimit::Limited => {
+                        match player_id {
+                            Some(id) => log_warn(format!("Player {} sends messages too fast", id)),
+                            None => log_warn("Connection sends messages too fast before login"),
+                        }
+                        send_error(
+                            &stream,
+                            ErrorCode::RateLimited,
+                            "Message dropped, slow down",
+                            request_id,
+                        );
+                        continue;
+                    }
+                    RateLimit::Exceeded => {
+                        let err = ConnectionError {
+                            err: "Too many messages sent while rate limited".into(),
+                            code: ErrorCode::RateLimited,
                             player_id,
-                        });
+                        };
+                        reject_client(&stream, &players, &err, &player_id, request_id);
+                        return Err(err);
                     }
+                }
 
-                    let player_id = player_id.as_ref().unwrap();
-                    match players_lock.get_mut(player_id) {
-                        Some(player) => player.role = Some(role),
-                        None => {
-                            let mut stream_lock = stream.lock().unwrap();
-                            log_warn(format!("Unwaranted id {}", player_id));
-                            if let Err(err) = stream_lock.shutdown() {
-                                log_error(err);
-                            };
-                            return Err(ConnectionError {
-                                err: "Unauthorized action".into(),
-                                player_id: Some(player_id.to_owned()),
-                            });
-                        }
-                    }
+                if let Err(err) = handle_message(
+                    &stream,
+                    &players,
+                    message,
+                    request_id,
+                    &mut player_id,
+                    config,
+                    peer,
+                ) {
+                    reject_client(&stream, &players, &err, &player_id, request_id);
+                    return Err(err);
                 }
-            },
+            }
             Err(MessageError::EmptyRead) => {
                 let one_mili = time::Duration::from_millis(1);
                 thread::sleep(one_mili);
             }
-            Err(MessageError::BadUnpack(e)) => {
-                log_error(e);
-                let mut stream_lock = stream.lock().unwrap();
-                if let Err(e) = stream_lock.shutdown() {
-                    log_error(e);
-                };
-
+            Err(MessageError::ConnectionClosed(_)) => {
                 if let Some(player_id) = player_id.as_ref() {
+                    log_info(format!("Player {} disconnected", player_id));
                     remove_player(players.clone(), player_id);
                 }
-                return Err(ConnectionError {
-                    err: e.to_string(),
-                    player_id,
-                });
+                return Ok(());
             }
-            Err(MessageError::InvalidRead(connection_type)) => {
+            Err(MessageError::InvalidRead(connection_type, e)) => {
                 match connection_type {
-                    ConnectionType::UnixSocket => log_error("Invalid unix socket read"),
-                    ConnectionType::Tcp => log_error("Invalid TCP read"),
+                    ConnectionType::UnixSocket => {
+                        log_error(format!("Invalid unix socket read: {}", e))
+                    }
+                    ConnectionType::Tcp => log_error(format!("Invalid TCP read: {}", e)),
+                    ConnectionType::InMemory => log_error(format!("Invalid in-memory read: {}", e)),
+                    ConnectionType::WebSocket => {
+                        log_error(format!("Invalid WebSocket read: {}", e))
+                    }
                 }
                 let mut stream_lock = stream.lock().unwrap();
                 if let Err(err) = stream_lock.write(&Message::Unknown) {
@@ -195,6 +351,7 @@ pub fn handle_game_client(
 
                 return Err(ConnectionError {
                     err: "Invalid data read".to_string(),
+                    code: ErrorCode::InvalidMessage,
                     player_id,
                 });
             }
@@ -202,6 +359,7 @@ pub fn handle_game_client(
                 if let Some(player_id) = player_id.as_ref() {
                     remove_player(players.clone(), player_id);
                 }
+                send_error(&stream, ErrorCode::InvalidMessage, &err.to_string(), None);
                 let mut stream_lock = stream.lock().unwrap();
                 if let Err(e) = stream_lock.shutdown() {
                     log_error(e);
@@ -209,6 +367,7 @@ pub fn handle_game_client(
 
                 return Err(ConnectionError {
                     err: err.to_string(),
+                    code: ErrorCode::InvalidMessage,
                     player_id,
                 });
             }
@@ -0,0 +1,130 @@
+use guessing_game::logging::{log_error, log_warn};
+use guessing_game::messages::{
+    ChatMessage, ChatScope, ErrorCode, Message, PlayerId, MAX_CHAT_MESSAGE_LENGTH,
+};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::{check_player, send_error, ConnectionError};
+use crate::player::Player;
+
+const CHAT_RATE_LIMIT: usize = 5;
+const CHAT_RATE_WINDOW: Duration = Duration::from_secs(10);
+
+fn is_rate_limited(player: &mut Player) -> bool {
+    let now = Instant::now();
+    while player
+        .chat_timestamps
+        .front()
+        .is_some_and(|sent_at| now.duration_since(*sent_at) > CHAT_RATE_WINDOW)
+    {
+        player.chat_timestamps.pop_front();
+    }
+
+    if player.chat_timestamps.len() >= CHAT_RATE_LIMIT {
+        return true;
+    }
+
+    player.chat_timestamps.push_back(now);
+    false
+}
+
+pub fn handle_chat_message(
+    players: Arc<Mutex<HashMap<PlayerId, Player>>>,
+    chat_message: ChatMessage,
+    player_id: &Option<PlayerId>,
+) -> Result<(), ConnectionError> {
+    if player_id.is_none() {
+        return Err(ConnectionError {
+            err: "Unauthorized action".into(),
+            code: ErrorCode::NotLoggedIn,
+            player_id: None,
+        });
+    }
+    let id = player_id.as_ref().unwrap();
+    check_player(&chat_message.sender, id)?;
+
+    let mut players_lock = players.lock().unwrap();
+    let player = players_lock.get_mut(id).ok_or(ConnectionError {
+        err: "Received chat message from not registered player".into(),
+        code: ErrorCode::NotLoggedIn,
+        player_id: *player_id,
+    })?;
+
+    let text = chat_message.text.trim();
+    if text.is_empty() || text.len() > MAX_CHAT_MESSAGE_LENGTH {
+        log_warn(format!(
+            "Dropping chat message of invalid length from {}",
+            id
+        ));
+        send_error(
+            &player.stream,
+            ErrorCode::InvalidMessage,
+            &format!(
+                "Chat message must have between 1 and {} characters",
+                MAX_CHAT_MESSAGE_LENGTH
+            ),
+            None,
+        );
+        return Ok(());
+    }
+
+    if is_rate_limited(player) {
+        log_warn(format!("Player {} exceeded chat rate limit", id));
+        send_error(
+            &player.stream,
+            ErrorCode::RateLimited,
+            "Chat message dropped, wait a few seconds",
+            None,
+        );
+        return Ok(());
+    }
+
+    let opponent_id = player
+        .opponent
+        .as_ref()
+        .map(|(opponent_id, _)| *opponent_id);
+    let player_stream = player.stream.clone();
+    let chat_message = ChatMessage {
+        sender: *id,
+        scope: chat_message.scope,
+        text: text.to_owned(),
+    };
+
+    match chat_message.scope {
+        ChatScope::Game => {
+            let Some(opponent_id) = opponent_id else {
+                log_warn(format!("Player {} sent game chat without opponent", id));
+                send_error(
+                    &player_stream,
+                    ErrorCode::NoOpponent,
+                    "Game chat needs an opponent",
+                    None,
+                );
+                return Ok(());
+            };
+
+            if let Some(opponent) = players_lock.get(&opponent_id) {
+                let mut opponent_stream_lock = opponent.stream.lock().unwrap();
+                if let Err(err) = opponent_stream_lock.write(&Message::ChatMessage(chat_message)) {
+                    log_error(err);
+                }
+            }
+        }
+        ChatScope::Lobby => {
+            players_lock
+                .values()
+                .filter(|player| &player.id != id && player.opponent.is_none())
+                .for_each(|player| {
+                    let mut stream_lock = player.stream.lock().unwrap();
+                    if let Err(err) = stream_lock.write(&Message::ChatMessage(chat_message.clone()))
+                    {
+                        log_error(err);
+                    }
+                });
+        }
+    }
+
+    Ok(())
+}
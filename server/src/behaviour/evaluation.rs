@@ -1,29 +1,45 @@
 use guessing_game::logging::log_warn;
-use guessing_game::messages::{Evaluation, Message, Role};
+use guessing_game::messages::{ErrorCode, Evaluation, Message, PlayerId, Role};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
 use super::ConnectionError;
+use crate::config::Config;
 use crate::player::Player;
 
+const MAX_SCORE: u16 = 100;
+const GUESS_PENALTY: u16 = 5;
+const HINT_PENALTY: u16 = 15;
+
+pub fn compute_score(guess_count: usize, hints_used: u8) -> u16 {
+    let extra_guesses = guess_count.saturating_sub(1).min(MAX_SCORE as usize) as u16;
+
+    MAX_SCORE
+        .saturating_sub(extra_guesses * GUESS_PENALTY)
+        .saturating_sub(hints_used as u16 * HINT_PENALTY)
+}
+
 pub fn handle_evaluation(
-    players: Arc<Mutex<HashMap<String, Player>>>,
-    evaluation: Evaluation,
-    player_id: &Option<String>,
+    players: Arc<Mutex<HashMap<PlayerId, Player>>>,
+    mut evaluation: Evaluation,
+    player_id: &Option<PlayerId>,
+    config: &Config,
 ) -> Result<(), ConnectionError> {
     if player_id.is_none() {
         return Err(ConnectionError {
             err: "Received evaluation for question from not registered player".into(),
+            code: ErrorCode::NotLoggedIn,
             player_id: None,
         });
     }
     let id = player_id.as_ref().unwrap();
-    let players_lock = players.lock().unwrap();
+    let mut players_lock = players.lock().unwrap();
     let player = players_lock.get(id);
 
     if player.is_none() {
         return Err(ConnectionError {
             err: "Received evaluation for question from not registered player".into(),
+            code: ErrorCode::NotLoggedIn,
             player_id: None,
         });
     }
@@ -33,6 +49,7 @@ pub fn handle_evaluation(
     if player.role.is_none() {
         return Err(ConnectionError {
             err: "Received evaluation for question from player without role".into(),
+            code: ErrorCode::WrongRole,
             player_id: None,
         });
     }
@@ -41,6 +58,7 @@ pub fn handle_evaluation(
     if player_role != &Role::AskingPlayer {
         return Err(ConnectionError {
             err: "Received evaluation for question from player without corresponding role".into(),
+            code: ErrorCode::WrongRole,
             player_id: None,
         });
     }
@@ -48,12 +66,35 @@ pub fn handle_evaluation(
     if player.opponent.is_none() {
         return Err(ConnectionError {
             err: "Received evaluation for question from player without opponent".into(),
+            code: ErrorCode::NoOpponent,
             player_id: None,
         });
     }
 
-    let (opponent_id, _) = player.opponent.as_ref().unwrap();
-    let opponent = players_lock.get(opponent_id);
+    let (opponent_id, guess_count) = player.opponent.unwrap();
+    let mut hints_used = player.hints_used;
+
+    if evaluation.hint.is_some() {
+        if hints_used < config.hint_budget {
+            hints_used += 1;
+        } else {
+            log_warn(format!("Player {} exceeded hint budget", id));
+            evaluation.hint = None;
+        }
+    }
+
+    evaluation.hints_remaining = config.hint_budget.saturating_sub(hints_used);
+    evaluation.score = if evaluation.guessed {
+        compute_score(guess_count, hints_used)
+    } else {
+        0
+    };
+
+    if let Some(player) = players_lock.get_mut(id) {
+        player.hints_used = hints_used;
+    }
+
+    let opponent = players_lock.get(&opponent_id);
 
     if opponent.is_none() {
         log_warn("Opponent is disconnected");
@@ -0,0 +1,79 @@
+use guessing_game::logging::{log_error, log_warn};
+use guessing_game::messages::{ErrorCode, Message, PlayerId, Role, Streamable, HOUSE_PLAYER_ID};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use super::house::handle_house_hint;
+use super::{send_error, ConnectionError};
+use crate::config::Config;
+use crate::player::Player;
+
+pub fn handle_request_hint(
+    stream: Arc<Mutex<dyn Streamable>>,
+    players: Arc<Mutex<HashMap<PlayerId, Player>>>,
+    player_id: &Option<PlayerId>,
+    config: &Config,
+) -> Result<(), ConnectionError> {
+    if player_id.is_none() {
+        return Err(ConnectionError {
+            err: "Unauthorized action".into(),
+            code: ErrorCode::NotLoggedIn,
+            player_id: None,
+        });
+    }
+    let id = player_id.as_ref().unwrap();
+    let players_lock = players.lock().unwrap();
+
+    let player = players_lock.get(id).ok_or(ConnectionError {
+        err: "Received hint request from not registered player".into(),
+        code: ErrorCode::NotLoggedIn,
+        player_id: *player_id,
+    })?;
+
+    if player.role.as_ref() != Some(&Role::GuessingPlayer) {
+        return Err(ConnectionError {
+            err: "Received hint request from player without corresponding role".into(),
+            code: ErrorCode::WrongRole,
+            player_id: *player_id,
+        });
+    }
+
+    let Some((opponent_id, _)) = player.opponent.as_ref() else {
+        log_warn(format!("Player {} requested hint without opponent", id));
+        send_error(
+            &stream,
+            ErrorCode::NoOpponent,
+            "Hints need a riddle to guess",
+            None,
+        );
+        return Ok(());
+    };
+
+    if *opponent_id == HOUSE_PLAYER_ID {
+        drop(players_lock);
+        return handle_house_hint(stream, players, id, config);
+    }
+
+    let Some(asking_player) = players_lock.get(opponent_id) else {
+        log_warn("Opponent is disconnected");
+        return Ok(());
+    };
+
+    if asking_player.hints_used >= config.hint_budget {
+        log_warn(format!("Player {} requested hint with budget spent", id));
+        send_error(
+            &stream,
+            ErrorCode::NoHintsLeft,
+            "Hint budget is spent",
+            None,
+        );
+        return Ok(());
+    }
+
+    let mut asking_player_stream_lock = asking_player.stream.lock().unwrap();
+    if let Err(err) = asking_player_stream_lock.write(&Message::RequestHint) {
+        log_error(err);
+    }
+
+    Ok(())
+}
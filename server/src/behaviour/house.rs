@@ -0,0 +1,183 @@
+use guessing_game::logging::{log_info, log_warn};
+use guessing_game::messages::{
+    ErrorCode, Evaluation, GameOver, GameOverReason, Message, PlayerId, Riddle, Role, Streamable,
+    HOUSE_PLAYER_ID,
+};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use super::evaluation::compute_score;
+use super::{send_error, ConnectionError};
+use crate::config::Config;
+use crate::player::Player;
+
+fn write_message(
+    stream: &Arc<Mutex<dyn Streamable>>,
+    message: &Message,
+    player_id: &PlayerId,
+) -> Result<(), ConnectionError> {
+    let mut stream_lock = stream.lock().unwrap();
+    stream_lock.write(message).map_err(|err| ConnectionError {
+        err: err.to_string(),
+        code: ErrorCode::Internal,
+        player_id: Some(*player_id),
+    })
+}
+
+pub fn handle_request_house_riddle(
+    stream: Arc<Mutex<dyn Streamable>>,
+    players: Arc<Mutex<HashMap<PlayerId, Player>>>,
+    player_id: &Option<PlayerId>,
+    config: &Config,
+) -> Result<(), ConnectionError> {
+    if player_id.is_none() {
+        return Err(ConnectionError {
+            err: "Unauthorized action".into(),
+            code: ErrorCode::NotLoggedIn,
+            player_id: None,
+        });
+    }
+    let id = player_id.as_ref().unwrap();
+    let mut players_lock = players.lock().unwrap();
+
+    let player = players_lock.get_mut(id).ok_or(ConnectionError {
+        err: "Received house riddle request from not registered player".into(),
+        code: ErrorCode::NotLoggedIn,
+        player_id: *player_id,
+    })?;
+
+    if player.role.as_ref() != Some(&Role::GuessingPlayer) || player.opponent.is_some() {
+        return Err(ConnectionError {
+            err: "Received house riddle request from player without corresponding role".into(),
+            code: ErrorCode::WrongRole,
+            player_id: *player_id,
+        });
+    }
+
+    let Some(library_riddle) = config.riddle_library.random(&player.preferred_categories) else {
+        log_warn("House riddle requested, but riddle library is empty");
+        return write_message(&stream, &Message::PlayerNotAvailable(HOUSE_PLAYER_ID), id);
+    };
+
+    log_info(format!(
+        "Serving {} riddle from category '{}' to {}",
+        library_riddle.difficulty, library_riddle.category, id
+    ));
+    let riddle = Riddle {
+        sender: HOUSE_PLAYER_ID,
+        asking_player: HOUSE_PLAYER_ID,
+        guessing_player: *id,
+        message: library_riddle.question.clone(),
+        hint: None,
+        max_guesses: config.max_guesses,
+        category: library_riddle.category.clone(),
+        difficulty: library_riddle.difficulty,
+    };
+    write_message(&stream, &Message::Riddle(riddle), id)?;
+
+    player.opponent = Some((HOUSE_PLAYER_ID, 0));
+    player.question = Some(library_riddle.question.clone());
+    player.category = library_riddle.category.clone();
+    player.max_guesses = config.max_guesses;
+    player.house_riddle = Some(library_riddle.clone());
+
+    Ok(())
+}
+
+pub fn handle_house_guess(
+    stream: Arc<Mutex<dyn Streamable>>,
+    players: Arc<Mutex<HashMap<PlayerId, Player>>>,
+    riddle: Riddle,
+    id: &PlayerId,
+    config: &Config,
+) -> Result<(), ConnectionError> {
+    let mut players_lock = players.lock().unwrap();
+    let player = players_lock.get_mut(id).ok_or(ConnectionError {
+        err: "Player not registered".into(),
+        code: ErrorCode::NotLoggedIn,
+        player_id: Some(*id),
+    })?;
+
+    let (Some((_, guess_count)), Some(library_riddle)) =
+        (player.opponent.as_mut(), player.house_riddle.as_ref())
+    else {
+        return Err(ConnectionError {
+            err: "Player is not playing against the house".into(),
+            code: ErrorCode::NoOpponent,
+            player_id: Some(*id),
+        });
+    };
+
+    *guess_count += 1;
+    let guess_count = *guess_count;
+    let hints_remaining = config.hint_budget.saturating_sub(player.hints_used);
+
+    if library_riddle.is_answer(&riddle.message) {
+        let evaluation = Evaluation {
+            guessed: true,
+            hints_remaining,
+            score: compute_score(guess_count, player.hints_used),
+            ..Default::default()
+        };
+        player.leave_game();
+        return write_message(&stream, &Message::Evaluation(evaluation), id);
+    }
+
+    if player.max_guesses > 0 && guess_count >= player.max_guesses as usize {
+        let game_over = GameOver {
+            winner: HOUSE_PLAYER_ID,
+            reason: GameOverReason::MaxGuessesReached,
+            answer: library_riddle.answers.first().cloned().unwrap_or_default(),
+        };
+        player.leave_game();
+        return write_message(&stream, &Message::GameOver(game_over), id);
+    }
+
+    let evaluation = Evaluation {
+        guessed: false,
+        hints_remaining,
+        ..Default::default()
+    };
+    write_message(&stream, &Message::Evaluation(evaluation), id)
+}
+
+pub fn handle_house_hint(
+    stream: Arc<Mutex<dyn Streamable>>,
+    players: Arc<Mutex<HashMap<PlayerId, Player>>>,
+    id: &PlayerId,
+    config: &Config,
+) -> Result<(), ConnectionError> {
+    let mut players_lock = players.lock().unwrap();
+    let player = players_lock.get_mut(id).ok_or(ConnectionError {
+        err: "Player not registered".into(),
+        code: ErrorCode::NotLoggedIn,
+        player_id: Some(*id),
+    })?;
+
+    let hint = player
+        .house_riddle
+        .as_ref()
+        .filter(|_| player.hints_used < config.hint_budget)
+        .and_then(|library_riddle| library_riddle.hints.get(player.hints_used as usize))
+        .cloned();
+
+    let Some(hint) = hint else {
+        log_warn(format!("Player {} requested hint with none left", id));
+        send_error(
+            &stream,
+            ErrorCode::NoHintsLeft,
+            "Riddle has no hints left",
+            None,
+        );
+        return Ok(());
+    };
+    player.hints_used += 1;
+
+    let evaluation = Evaluation {
+        hint: Some(hint),
+        guessed: false,
+        hints_remaining: config.hint_budget.saturating_sub(player.hints_used),
+        ..Default::default()
+    };
+    write_message(&stream, &Message::Evaluation(evaluation), id)
+}
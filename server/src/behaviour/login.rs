@@ -1,44 +1,63 @@
 use super::ConnectionError;
+use crate::config::Config;
 use crate::player::Player;
-use guessing_game::messages::{get_random_id, Message, Password, PlayerId, Streamable};
+use guessing_game::messages::{ErrorCode, Frame, Message, Password, PlayerId, Streamable};
 use std::collections::HashMap;
+use std::net::IpAddr;
 use std::sync::{Arc, Mutex};
 
 pub fn handle_login(
     stream: Arc<Mutex<dyn Streamable>>,
-    players: Arc<Mutex<HashMap<String, Player>>>,
+    players: Arc<Mutex<HashMap<PlayerId, Player>>>,
     password: Password,
-    password_secret: &str,
-) -> Result<Option<String>, ConnectionError> {
+    config: &Config,
+    peer: Option<IpAddr>,
+    request_id: Option<u32>,
+) -> Result<Option<PlayerId>, ConnectionError> {
+    let login_guard = &config.login_guard;
+    if let Some(wait) = peer.and_then(|peer| login_guard.login_wait(peer)) {
+        return Err(ConnectionError {
+            err: format!(
+                "Too many failed logins, retry in {}s",
+                wait.as_secs_f32().ceil()
+            ),
+            code: ErrorCode::RateLimited,
+            player_id: None,
+        });
+    }
+
     let mut stream_lock = stream.lock().unwrap();
-    if password.password.trim() != password_secret {
+    if password.password.trim() != config.password {
+        if let Some(peer) = peer {
+            login_guard.login_failed(peer);
+        }
         return Err(ConnectionError {
             err: "Invalid login attempt".to_string(),
+            code: ErrorCode::InvalidPassword,
             player_id: None,
         });
     }
 
+    if let Some(peer) = peer {
+        login_guard.login_succeeded(peer);
+    }
+
     let mut players_lock = players.lock().unwrap();
-    let player_id = get_random_id();
-    let player_id_instance = PlayerId {
-        player_id: player_id.clone(),
-    };
+    let player_id = PlayerId::random();
 
-    match stream_lock.write(&Message::PlayerId(player_id_instance)) {
+    match stream_lock.write_frame(&Frame {
+        request_id,
+        message: Message::PlayerId(player_id),
+    }) {
         Ok(_) => {
-            let new_player = Player {
-                id: player_id.clone(),
-                stream: stream.clone(),
-                opponent: None,
-                question: None,
-                role: None,
-            };
-            players_lock.insert(player_id.clone(), new_player);
+            let new_player = Player::new(player_id, stream.clone());
+            players_lock.insert(player_id, new_player);
 
             Ok(Some(player_id))
         }
         Err(err) => Err(ConnectionError {
             err: err.to_string(),
+            code: ErrorCode::Internal,
             player_id: Some(player_id),
         }),
     }
@@ -1,25 +1,27 @@
 use guessing_game::logging::{log_error, log_warn};
-use guessing_game::messages::{Message, OpponentSelected, Role, Streamable};
+use guessing_game::messages::{ErrorCode, Message, OpponentSelected, PlayerId, Role, Streamable};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
-use super::ConnectionError;
+use super::{check_player, ConnectionError};
 use crate::player::Player;
 
 pub fn handle_opponent_selected(
     stream: Arc<Mutex<dyn Streamable>>,
-    players: Arc<Mutex<HashMap<String, Player>>>,
+    players: Arc<Mutex<HashMap<PlayerId, Player>>>,
     opponent_selected: OpponentSelected,
-    player_id: &Option<String>,
+    player_id: &Option<PlayerId>,
 ) -> Result<(), ConnectionError> {
     if player_id.is_none() {
         return Err(ConnectionError {
             err: "Unauthorized action".into(),
+            code: ErrorCode::NotLoggedIn,
             player_id: None,
         });
     }
 
     let player_id = player_id.as_ref().unwrap();
+    check_player(&opponent_selected.guessing_player, player_id)?;
     let players_lock = players.lock().unwrap();
     let opponent = players_lock.get(&opponent_selected.asking_player);
 
@@ -44,8 +46,8 @@ pub fn handle_opponent_selected(
 
     let opponent = opponent.unwrap();
     let opponent_selected = OpponentSelected {
-        guessing_player: player_id.into(),
-        asking_player: opponent.id.clone(),
+        guessing_player: *player_id,
+        asking_player: opponent.id,
     };
 
     let mut opponent_stream_lock = opponent.stream.lock().unwrap();
@@ -0,0 +1,141 @@
+use guessing_game::messages::Message;
+use std::collections::HashMap;
+use std::mem::{self, Discriminant};
+use std::time::Instant;
+
+/// Messages refused before the connection is closed, refilled at one per second.
+const MAX_STRIKES: f32 = 20.0;
+
+struct TokenBucket {
+    capacity: f32,
+    per_second: f32,
+    tokens: f32,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    fn new(capacity: f32, per_second: f32, now: Instant) -> Self {
+        TokenBucket {
+            capacity,
+            per_second,
+            tokens: capacity,
+            refilled_at: now,
+        }
+    }
+
+    fn try_take(&mut self, now: Instant) -> bool {
+        let elapsed = now.duration_since(self.refilled_at).as_secs_f32();
+        self.tokens = (self.tokens + elapsed * self.per_second).min(self.capacity);
+        self.refilled_at = now;
+
+        if self.tokens < 1.0 {
+            return false;
+        }
+        self.tokens -= 1.0;
+        true
+    }
+}
+
+/// Burst size and messages per second allowed for the type of `message`.
+fn limit(message: &Message) -> (f32, f32) {
+    match message {
+        // both take the players lock and scan or update every player
+        Message::RequestGuessingPlayers(_) => (5.0, 1.0),
+        Message::Riddle(_) | Message::Evaluation(_) => (10.0, 2.0),
+        Message::Password(_) => (3.0, 0.2),
+        _ => (20.0, 5.0),
+    }
+}
+
+pub enum RateLimit {
+    Allowed,
+    /// Message is dropped, the client is told to slow down.
+    Limited,
+    /// Client kept sending while limited and is disconnected.
+    Exceeded,
+}
+
+/// Token buckets of one connection, one per message type.
+pub struct RateLimiter {
+    buckets: HashMap<Discriminant<Message>, TokenBucket>,
+    strikes: TokenBucket,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        RateLimiter {
+            buckets: HashMap::new(),
+            strikes: TokenBucket::new(MAX_STRIKES, 1.0, Instant::now()),
+        }
+    }
+}
+
+impl RateLimiter {
+    pub fn check(&mut self, message: &Message) -> RateLimit {
+        self.check_at(message, Instant::now())
+    }
+
+    fn check_at(&mut self, message: &Message, now: Instant) -> RateLimit {
+        let bucket = self
+            .buckets
+            .entry(mem::discriminant(message))
+            .or_insert_with(|| {
+                let (capacity, per_second) = limit(message);
+                TokenBucket::new(capacity, per_second, now)
+            });
+
+        if bucket.try_take(now) {
+            RateLimit::Allowed
+        } else if self.strikes.try_take(now) {
+            RateLimit::Limited
+        } else {
+            RateLimit::Exceeded
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn bursts_are_limited_per_message_type() {
+        let mut rate_limiter = RateLimiter::default();
+        let now = Instant::now();
+
+        for _ in 0..5 {
+            assert!(matches!(
+                rate_limiter.check_at(&Message::RequestGuessingPlayers(None), now),
+                RateLimit::Allowed
+            ));
+        }
+        assert!(matches!(
+            rate_limiter.check_at(&Message::RequestGuessingPlayers(None), now),
+            RateLimit::Limited
+        ));
+        assert!(matches!(
+            rate_limiter.check_at(&Message::RequestHint, now),
+            RateLimit::Allowed
+        ));
+        assert!(matches!(
+            rate_limiter.check_at(
+                &Message::RequestGuessingPlayers(None),
+                now + Duration::from_secs(1)
+            ),
+            RateLimit::Allowed
+        ));
+    }
+
+    #[test]
+    fn persistent_flooding_exceeds_the_limit() {
+        let mut rate_limiter = RateLimiter::default();
+        let now = Instant::now();
+
+        let results = (0..30)
+            .map(|_| rate_limiter.check_at(&Message::RequestGuessingPlayers(None), now))
+            .collect::<Vec<RateLimit>>();
+        assert!(matches!(results[24], RateLimit::Limited));
+        assert!(matches!(results[25], RateLimit::Exceeded));
+    }
+}
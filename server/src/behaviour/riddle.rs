@@ -1,14 +1,19 @@
 use guessing_game::logging::log_warn;
-use guessing_game::messages::{Evaluation, Message, Riddle, Streamable};
+use guessing_game::messages::{
+    ErrorCode, Evaluation, GameOver, GameOverReason, Message, PlayerId, Riddle, Role, Streamable,
+    HOUSE_PLAYER_ID,
+};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
-use super::ConnectionError;
+use super::house::handle_house_guess;
+use super::{check_player, ConnectionError};
+use crate::config::Config;
 use crate::player::Player;
 
 fn increase_guess_count(
-    players: Arc<Mutex<HashMap<String, Player>>>,
-    player_id: &str,
+    players: Arc<Mutex<HashMap<PlayerId, Player>>>,
+    player_id: &PlayerId,
 ) -> Result<(), &'static str> {
     let mut players_lock = players.lock().unwrap();
     let player = players_lock
@@ -20,39 +25,87 @@ fn increase_guess_count(
         .as_ref()
         .ok_or("Opponent not in players list")?;
 
-    player.opponent = Some((opponent.to_string(), *guess_count + 1));
+    player.opponent = Some((*opponent, *guess_count + 1));
 
     Ok(())
 }
 
 pub fn handle_riddle(
     stream: Arc<Mutex<dyn Streamable>>,
-    players: Arc<Mutex<HashMap<String, Player>>>,
-    riddle: Riddle,
-    player_id: &Option<String>,
+    players: Arc<Mutex<HashMap<PlayerId, Player>>>,
+    mut riddle: Riddle,
+    player_id: &Option<PlayerId>,
+    config: &Config,
 ) -> Result<(), ConnectionError> {
     if player_id.is_none() {
         return Err(ConnectionError {
             err: "Received evaluation for question from not registered player".into(),
+            code: ErrorCode::NotLoggedIn,
             player_id: None,
         });
     }
     let id = player_id.as_ref().unwrap();
+    check_player(&riddle.sender, id)?;
+
+    let (role, opponent_id) = {
+        let players_lock = players.lock().unwrap();
+        let player = players_lock.get(id).ok_or(ConnectionError {
+            err: "Received riddle from not registered player".into(),
+            code: ErrorCode::NotLoggedIn,
+            player_id: *player_id,
+        })?;
+        (
+            player.role,
+            player.opponent.map(|(opponent_id, _)| opponent_id),
+        )
+    };
 
     if id == &riddle.asking_player {
+        if role != Some(Role::AskingPlayer) {
+            return Err(ConnectionError {
+                err: "Received riddle from player without corresponding role".into(),
+                code: ErrorCode::WrongRole,
+                player_id: *player_id,
+            });
+        }
+
+        if riddle.guessing_player == *id {
+            return Err(ConnectionError {
+                err: "Received riddle addressed to its own sender".into(),
+                code: ErrorCode::Forbidden,
+                player_id: *player_id,
+            });
+        }
+
         let mut players_lock = players.lock().unwrap();
-        let guessing_player = players_lock.get(&riddle.guessing_player);
-        if guessing_player.is_none() {
+        let available = players_lock
+            .get(&riddle.guessing_player)
+            .is_some_and(|guessing_player| {
+                guessing_player.role == Some(Role::GuessingPlayer)
+                    && guessing_player.opponent.is_none()
+            });
+        if !available {
             let mut stream_lock = stream.lock().unwrap();
             stream_lock
                 .write(&Message::PlayerNotAvailable(riddle.guessing_player))
                 .map_err(|err| ConnectionError {
                     err: err.to_string(),
+                    code: ErrorCode::Internal,
                     player_id: Some(riddle.asking_player),
                 })?;
             return Ok(());
         }
 
+        if riddle.hint.is_some() && config.hint_budget == 0 {
+            log_warn("Hint budget is empty, dropping hint from riddle");
+            riddle.hint = None;
+        }
+        let hints_used = riddle.hint.is_some() as u8;
+
+        if riddle.max_guesses == 0 {
+            riddle.max_guesses = config.max_guesses;
+        }
+
         {
             let guessing_player = players_lock.get_mut(&riddle.guessing_player).unwrap();
             let mut guessing_player_stream_lock = guessing_player.stream.lock().unwrap();
@@ -60,57 +113,122 @@ pub fn handle_riddle(
                 log_warn(err);
                 return Ok(());
             }
-            guessing_player.opponent = Some((id.into(), 0));
-            guessing_player.question = Some(riddle.message.clone())
+            guessing_player.opponent = Some((*id, 0));
+            guessing_player.question = Some(riddle.message.clone());
+            guessing_player.category = riddle.category.clone();
         }
 
         let asking_player = players_lock.get_mut(id).unwrap();
-        asking_player.opponent = Some((riddle.guessing_player.clone(), 0));
+        asking_player.opponent = Some((riddle.guessing_player, 0));
         asking_player.question = Some(riddle.message);
+        asking_player.category = riddle.category;
+        asking_player.hints_used = hints_used;
+        asking_player.max_guesses = riddle.max_guesses;
 
         return Ok(());
     }
 
-    // guessing player
+    // guessing player, the asking player is taken from the game the server keeps
+
+    check_player(&riddle.guessing_player, id)?;
+    if role != Some(Role::GuessingPlayer) {
+        return Err(ConnectionError {
+            err: "Received guess from player without corresponding role".into(),
+            code: ErrorCode::WrongRole,
+            player_id: *player_id,
+        });
+    }
+    let opponent_id = opponent_id.ok_or(ConnectionError {
+        err: "Received guess from player without opponent".into(),
+        code: ErrorCode::NoOpponent,
+        player_id: *player_id,
+    })?;
+    if riddle.asking_player != opponent_id {
+        log_warn(format!(
+            "Player {} guessed for {} while playing against {}",
+            id, riddle.asking_player, opponent_id
+        ));
+        return Err(ConnectionError {
+            err: format!(
+                "Guess names player {} instead of the opponent",
+                riddle.asking_player
+            ),
+            code: ErrorCode::Forbidden,
+            player_id: *player_id,
+        });
+    }
+
+    if opponent_id == HOUSE_PLAYER_ID {
+        return handle_house_guess(stream, players, riddle, id, config);
+    }
 
     increase_guess_count(players.clone(), id).map_err(|err| ConnectionError {
         err: err.to_string(),
-        player_id: player_id.clone(),
+        code: ErrorCode::NoOpponent,
+        player_id: *player_id,
     })?;
 
-    increase_guess_count(players.clone(), &riddle.asking_player).map_err(|err| {
-        ConnectionError {
-            err: err.to_string(),
-            player_id: player_id.clone(),
-        }
+    increase_guess_count(players.clone(), &opponent_id).map_err(|err| ConnectionError {
+        err: err.to_string(),
+        code: ErrorCode::NoOpponent,
+        player_id: *player_id,
     })?;
 
     let mut players_lock = players.lock().unwrap();
 
-    let asking_player = players_lock
-        .get_mut(&riddle.asking_player)
-        .ok_or(ConnectionError {
-            err: "Opponent not registered".into(),
-            player_id: player_id.clone(),
-        })?;
-
-    let mut asking_player_stream_lock = asking_player.stream.lock().unwrap();
+    let asking_player = players_lock.get_mut(&opponent_id).ok_or(ConnectionError {
+        err: "Opponent not registered".into(),
+        code: ErrorCode::NoOpponent,
+        player_id: *player_id,
+    })?;
 
-    let question = asking_player.question.as_ref().ok_or(ConnectionError {
+    let question = asking_player.question.clone().ok_or(ConnectionError {
         err: "Player is missing question".into(),
-        player_id: player_id.clone(),
+        code: ErrorCode::NoOpponent,
+        player_id: *player_id,
     })?;
+    let guessed = question == riddle.message;
+    let guess_count = asking_player
+        .opponent
+        .as_ref()
+        .map_or(0, |(_, count)| *count);
+
+    if !guessed
+        && asking_player.max_guesses > 0
+        && guess_count >= asking_player.max_guesses as usize
+    {
+        let game_over = GameOver {
+            winner: asking_player.id,
+            reason: GameOverReason::MaxGuessesReached,
+            answer: question,
+        };
+
+        for game_player_id in [&opponent_id, id] {
+            if let Some(game_player) = players_lock.get_mut(game_player_id) {
+                game_player.leave_game();
+
+                let mut stream_lock = game_player.stream.lock().unwrap();
+                if let Err(err) = stream_lock.write(&Message::GameOver(game_over.clone())) {
+                    log_warn(err);
+                }
+            }
+        }
+
+        return Ok(());
+    }
 
     let evaluation = Evaluation {
-        hint: None,
-        guessed: question == &riddle.message,
+        guessed,
+        ..Default::default()
     };
 
+    let mut asking_player_stream_lock = asking_player.stream.lock().unwrap();
     asking_player_stream_lock
         .write(&Message::Evaluation(evaluation))
         .map_err(|err| ConnectionError {
             err: err.to_string(),
-            player_id: player_id.clone(),
+            code: ErrorCode::Internal,
+            player_id: *player_id,
         })?;
     Ok(())
 }
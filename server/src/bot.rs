@@ -0,0 +1,114 @@
+mod asking;
+mod guessing;
+
+use crate::behaviour::handle_game_client;
+use crate::config::Config;
+use crate::player::Player;
+use asking::play_asking_game;
+use guessing::play_guessing_game;
+use guessing_game::client_core::{ClientError, GameClient, GameEvent};
+use guessing_game::logging::{log_error, log_info};
+use guessing_game::messages::{
+    memory_stream_pair, MemoryMessageStream, PlayerId, PlayerRole, Role,
+};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::{thread, time};
+
+pub struct BotSession {
+    pub client: GameClient<MemoryMessageStream>,
+    pub player_id: PlayerId,
+}
+
+/// Connects a bot through an in-memory stream, so it is served like any other client
+/// without depending on the listeners.
+fn connect(
+    players: Arc<Mutex<HashMap<PlayerId, Player>>>,
+    config: Arc<Config>,
+    player_role: PlayerRole,
+) -> Result<BotSession, ClientError> {
+    let (client_stream, server_stream) = memory_stream_pair();
+    let password = config.password.clone();
+    thread::spawn(move || {
+        if let Err(err) = handle_game_client(server_stream, players, &config, None) {
+            log_error(err.err);
+        }
+    });
+
+    let mut client = GameClient::new(client_stream);
+    let player_id = client.login(&password)?;
+    client.register_role(player_role)?;
+
+    Ok(BotSession { client, player_id })
+}
+
+/// Returns next event, bots do not take part in chats.
+pub fn next_event(session: &mut BotSession) -> Result<GameEvent, String> {
+    loop {
+        match session.client.next_event().map_err(|e| e.to_string())? {
+            GameEvent::Chat(_) => continue,
+            event => return Ok(event),
+        }
+    }
+}
+
+fn run_bot(
+    name: String,
+    players: Arc<Mutex<HashMap<PlayerId, Player>>>,
+    config: Arc<Config>,
+    role: Role,
+) {
+    loop {
+        let mut session = match connect(players.clone(), config.clone(), role.into()) {
+            Ok(session) => session,
+            Err(err) => {
+                log_error(format!("{} could not connect: {}", name, err));
+                return;
+            }
+        };
+        log_info(format!("{} joined as {}", name, session.player_id));
+
+        let result = match role {
+            Role::AskingPlayer => play_asking_game(&mut session, &config),
+            Role::GuessingPlayer => play_guessing_game(&mut session, &config),
+        };
+        if let Err(err) = result {
+            log_error(format!("{}: {}", name, err));
+        }
+        let _ = session.client.shutdown();
+
+        thread::sleep(time::Duration::from_secs(1));
+    }
+}
+
+pub fn spawn_bots(players: Arc<Mutex<HashMap<PlayerId, Player>>>, config: Arc<Config>) {
+    if config.asking_bots > 0 && config.riddle_library.is_empty() {
+        log_error("Asking bots need a riddle library, use --riddle-dir=<path>");
+    } else {
+        for index in 0..config.asking_bots {
+            let players = players.clone();
+            let config = config.clone();
+            thread::spawn(move || {
+                run_bot(
+                    format!("Asking bot {}", index),
+                    players,
+                    config,
+                    Role::AskingPlayer,
+                )
+            });
+        }
+    }
+
+    for index in 0..config.guessing_bots {
+        let players = players.clone();
+        let config = config.clone();
+        thread::spawn(move || {
+            run_bot(
+                format!("Guessing bot {}", index),
+                players,
+                config,
+                Role::GuessingPlayer,
+            )
+        });
+    }
+}
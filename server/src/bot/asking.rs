@@ -0,0 +1,76 @@
+use super::{next_event, BotSession};
+use crate::config::Config;
+use guessing_game::client_core::GameEvent;
+use guessing_game::logging::log_info;
+use guessing_game::messages::{PlayerId, Riddle};
+use rand::seq::SliceRandom;
+use std::{thread, time};
+
+fn find_guessing_player(session: &mut BotSession) -> Result<PlayerId, String> {
+    loop {
+        let opponent_ids = session
+            .client
+            .list_opponents(None)
+            .map_err(|e| e.to_string())?;
+        if let Some(guessing_player) = opponent_ids.choose(&mut rand::thread_rng()) {
+            return Ok(*guessing_player);
+        }
+
+        thread::sleep(time::Duration::from_secs(2));
+    }
+}
+
+pub fn play_asking_game(session: &mut BotSession, config: &Config) -> Result<(), String> {
+    let library_riddle = config
+        .riddle_library
+        .random(&[])
+        .ok_or("Riddle library is empty")?;
+    let answer = library_riddle
+        .answers
+        .first()
+        .ok_or("Library riddle has no answer")?;
+
+    loop {
+        let guessing_player = find_guessing_player(session)?;
+        let mut hints = library_riddle.hints.iter();
+        let riddle = Riddle {
+            guessing_player,
+            message: answer.clone(),
+            hint: Some(library_riddle.question.clone()),
+            max_guesses: 0,
+            category: library_riddle.category.clone(),
+            difficulty: library_riddle.difficulty,
+            ..Default::default()
+        };
+        session
+            .client
+            .send_riddle(riddle)
+            .map_err(|e| e.to_string())?;
+
+        loop {
+            let (guessed, hint) = match next_event(session)? {
+                GameEvent::GuessEvaluated { guessed: true } => {
+                    log_info(format!("Bot riddle guessed by {}", guessing_player));
+                    (true, None)
+                }
+                GameEvent::GuessEvaluated { .. } | GameEvent::HintRequested => {
+                    (false, hints.next().cloned())
+                }
+                GameEvent::PlayerNotAvailable(id) if id == guessing_player => break,
+                GameEvent::GameOver(_) => {
+                    log_info(format!("Bot riddle not guessed by {}", guessing_player));
+                    return Ok(());
+                }
+                _ => continue,
+            };
+
+            session
+                .client
+                .evaluate(guessed, hint)
+                .map_err(|e| e.to_string())?;
+            if guessed {
+                return Ok(());
+            }
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use super::{next_event, BotSession};
+use crate::config::Config;
+use guessing_game::client_core::GameEvent;
+use guessing_game::logging::log_info;
+use std::collections::VecDeque;
+use std::{thread, time};
+
+const GUESS_DELAY: time::Duration = time::Duration::from_millis(500);
+
+fn add_candidates(candidates: &mut VecDeque<String>, guessed: &[String], text: &str) {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.len() > 2)
+        .map(|word| word.to_lowercase())
+        .for_each(|word| {
+            if !candidates.contains(&word) && !guessed.contains(&word) {
+                candidates.push_back(word);
+            }
+        });
+}
+
+pub fn play_guessing_game(session: &mut BotSession, config: &Config) -> Result<(), String> {
+    let riddle = loop {
+        if let GameEvent::RiddleReceived(riddle) = next_event(session)? {
+            break riddle;
+        }
+    };
+
+    let mut guessed = Vec::new();
+    let mut candidates = VecDeque::new();
+    [Some(&riddle.message), riddle.hint.as_ref()]
+        .into_iter()
+        .flatten()
+        .filter_map(|clue| config.riddle_library.find_by_question(clue))
+        .flat_map(|library_riddle| library_riddle.answers.iter())
+        .for_each(|answer| add_candidates(&mut candidates, &guessed, answer));
+    if let Some(hint) = riddle.hint.as_ref() {
+        add_candidates(&mut candidates, &guessed, hint);
+    }
+    config
+        .riddle_library
+        .answers()
+        .for_each(|answer| add_candidates(&mut candidates, &guessed, answer));
+
+    loop {
+        let Some(candidate) = candidates.pop_front() else {
+            log_info(format!("Bot {} ran out of guesses", session.player_id));
+            return Ok(());
+        };
+
+        thread::sleep(GUESS_DELAY);
+        session
+            .client
+            .guess(&candidate)
+            .map_err(|e| e.to_string())?;
+        guessed.push(candidate);
+
+        match next_event(session)? {
+            GameEvent::Evaluated(evaluation) if evaluation.guessed => {
+                log_info(format!("Bot {} guessed the riddle", session.player_id));
+                return Ok(());
+            }
+            GameEvent::Evaluated(evaluation) => {
+                if let Some(hint) = evaluation.hint.as_ref() {
+                    let mut hint_candidates = VecDeque::new();
+                    add_candidates(&mut hint_candidates, &guessed, hint);
+                    hint_candidates.extend(candidates);
+                    candidates = hint_candidates;
+                }
+            }
+            _ => return Ok(()),
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use crate::login_guard::LoginGuard;
+use crate::password::get_password;
+use crate::riddle_library::RiddleLibrary;
+use guessing_game::logging::{log_info, log_warn};
+use guessing_game::messages::ServerTls;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use std::{env, fs};
+
+const DEFAULT_HINT_BUDGET: u8 = 3;
+const DEFAULT_MAX_GUESSES: u8 = 10;
+const DEFAULT_LOGIN_ATTEMPTS: u32 = 5;
+const DEFAULT_LOGIN_LOCKOUT_SECS: u64 = 300;
+
+pub struct Config {
+    pub password: String,
+    pub hint_budget: u8,
+    /// Guesses allowed per riddle when the riddle does not set its own limit, 0 means unlimited.
+    pub max_guesses: u8,
+    pub riddle_library: RiddleLibrary,
+    pub asking_bots: u8,
+    pub guessing_bots: u8,
+    /// Directory receiving one message recording per connection.
+    pub record_dir: Option<PathBuf>,
+    /// TLS for the TCP listener, plain TCP when not set.
+    pub tls: Option<ServerTls>,
+    /// Connection cap and failed login throttling per peer address.
+    pub login_guard: LoginGuard,
+}
+
+fn get_arg(prefix: &str) -> Option<String> {
+    env::args()
+        .find(|arg| arg.starts_with(prefix))
+        .map(|arg| arg.trim_start_matches(prefix).to_string())
+}
+
+fn get_numeric_arg<T: std::str::FromStr>(prefix: &str, default: T) -> T {
+    match get_arg(prefix) {
+        Some(value) => value
+            .parse()
+            .unwrap_or_else(|_| panic!("{} expects a number, got {}", prefix, value)),
+        None => default,
+    }
+}
+
+fn get_riddle_library() -> RiddleLibrary {
+    let Some(riddle_dir) = get_arg("--riddle-dir=") else {
+        return RiddleLibrary::default();
+    };
+
+    let library =
+        RiddleLibrary::load_dir(Path::new(&riddle_dir)).unwrap_or_else(|err| panic!("{}", err));
+    if library.is_empty() {
+        log_warn(format!("No riddles found in {}", riddle_dir));
+    } else {
+        log_info(format!(
+            "Loaded {} riddles from {}",
+            library.len(),
+            riddle_dir
+        ));
+    }
+
+    library
+}
+
+fn get_record_dir() -> Option<PathBuf> {
+    let record_dir = PathBuf::from(get_arg("--record-dir=")?);
+    fs::create_dir_all(&record_dir).unwrap_or_else(|err| panic!("{}", err));
+    log_info(format!("Recording connections to {}", record_dir.display()));
+
+    Some(record_dir)
+}
+
+fn get_tls() -> Option<ServerTls> {
+    let tls = if env::args().any(|arg| arg == "--tls-self-signed") {
+        ServerTls::self_signed(vec!["localhost".into(), "127.0.0.1".into()])
+    } else {
+        match (get_arg("--tls-cert="), get_arg("--tls-key=")) {
+            (Some(certificate), Some(key)) => {
+                ServerTls::from_pem_files(Path::new(&certificate), Path::new(&key))
+            }
+            (None, None) => return None,
+            _ => panic!("--tls-cert and --tls-key have to be provided together"),
+        }
+    };
+
+    let tls = tls.unwrap_or_else(|err| panic!("{}", err));
+    log_info(format!(
+        "TLS enabled, certificate fingerprint: {}",
+        tls.fingerprint
+    ));
+
+    Some(tls)
+}
+
+pub fn get_config() -> Config {
+    Config {
+        password: get_password(),
+        hint_budget: get_numeric_arg("--hint-budget=", DEFAULT_HINT_BUDGET),
+        max_guesses: get_numeric_arg("--max-guesses=", DEFAULT_MAX_GUESSES),
+        riddle_library: get_riddle_library(),
+        asking_bots: get_numeric_arg("--asking-bots=", 0),
+        guessing_bots: get_numeric_arg("--guessing-bots=", 0),
+        record_dir: get_record_dir(),
+        tls: get_tls(),
+        login_guard: LoginGuard::new(
+            get_numeric_arg("--max-connections-per-peer=", 0),
+            get_numeric_arg("--login-attempts=", DEFAULT_LOGIN_ATTEMPTS),
+            Duration::from_secs(get_numeric_arg(
+                "--login-lockout-secs=",
+                DEFAULT_LOGIN_LOCKOUT_SECS,
+            )),
+        ),
+    }
+}
@@ -1,14 +1,113 @@
-use guessing_game::messages::Role;
+use guessing_game::messages::{PlayerId, Role, HOUSE_PLAYER_ID};
 
+use crate::config::Config;
 use crate::player::Player;
+use crate::websocket::handle_websocket_client;
+use std::io::BufReader;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use std::{collections::HashMap, io::prelude::*, net::TcpStream};
 
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+const MAX_HEADER_LINES: usize = 64;
+const PLAY_PAGE: &str = include_str!("http/play.html");
+
+struct Request {
+    path: String,
+    websocket_key: Option<String>,
+}
+
+fn read_request(reader: &mut impl BufRead) -> Result<Request, &'static str> {
+    let mut request_line = String::new();
+    reader
+        .read_line(&mut request_line)
+        .map_err(|_| "error reading http request")?;
+    let path = request_line
+        .split_whitespace()
+        .nth(1)
+        .ok_or("invalid http request line")?
+        .to_owned();
+
+    let mut upgrade = false;
+    let mut websocket_key = None;
+    for _ in 0..MAX_HEADER_LINES {
+        let mut line = String::new();
+        reader
+            .read_line(&mut line)
+            .map_err(|_| "error reading http request")?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            return Ok(Request {
+                path,
+                websocket_key: websocket_key.filter(|_| upgrade),
+            });
+        }
+
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        match name.trim().to_lowercase().as_str() {
+            "upgrade" => upgrade = value.trim().eq_ignore_ascii_case("websocket"),
+            "sec-websocket-key" => websocket_key = Some(value.trim().to_owned()),
+            _ => {}
+        }
+    }
+
+    Err("too many http request headers")
+}
+
+fn write_response(
+    stream: &mut TcpStream,
+    status_line: &str,
+    body: &str,
+) -> Result<(), &'static str> {
+    let length = body.len();
+    let response = format!(
+        "{status_line}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {length}\r\n\r\n{body}"
+    );
+    stream
+        .write_all(response.as_bytes())
+        .map_err(|_| "error writing http request")
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Serves the dashboard and the browser client, and upgrades `/ws` to a game connection.
 pub fn handle_http_request(
-    mut stream: TcpStream,
-    players: Arc<Mutex<HashMap<String, Player>>>,
+    stream: TcpStream,
+    players: Arc<Mutex<HashMap<PlayerId, Player>>>,
+    config: Arc<Config>,
 ) -> Result<(), &'static str> {
-    let status_line = "HTTP/1.1 200 OK";
+    stream
+        .set_read_timeout(Some(REQUEST_TIMEOUT))
+        .map_err(|_| "error setting http read timeout")?;
+    let mut reader = BufReader::new(stream);
+    let request = read_request(&mut reader)?;
+    let read_ahead = reader.buffer().to_vec();
+    let mut stream = reader.into_inner();
+
+    match (request.path.as_str(), request.websocket_key) {
+        ("/ws", Some(websocket_key)) => {
+            handle_websocket_client(stream, &websocket_key, read_ahead, players, config);
+            Ok(())
+        }
+        ("/ws", None) => write_response(
+            &mut stream,
+            "HTTP/1.1 400 Bad Request",
+            "Expected a WebSocket upgrade",
+        ),
+        ("/play", _) => write_response(&mut stream, "HTTP/1.1 200 OK", PLAY_PAGE),
+        _ => write_response(&mut stream, "HTTP/1.1 200 OK", &dashboard(players)),
+    }
+}
+
+fn dashboard(players: Arc<Mutex<HashMap<PlayerId, Player>>>) -> String {
     let mut html = r##"
             <!doctype html>
             <html lang="en">
@@ -17,12 +116,14 @@ pub fn handle_http_request(
                 <title>Guessing game</title>
               </head>
               <body>
+              <p><a href="/play">Play in the browser</a></p>
               <table>
               <thead>
                 <tr>
                     <th>Asking Player</th>
                     <th>Guessing Player</th>
                     <th>Guesses</th>
+                    <th>Category</th>
                 </tr>
              </thead>
              <tbody>
@@ -30,20 +131,27 @@ pub fn handle_http_request(
     .to_string();
     let players_lock = players.lock().unwrap();
     players_lock.values().for_each(|player| {
-        if player.opponent.is_some()
-            && player
-                .role
-                .as_ref()
-                .is_some_and(|role| role == &Role::AskingPlayer)
-        {
-            let (opponent_id, guesses) = player.opponent.as_ref().unwrap();
-            let table_row = format!(
-                "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
-                &player.id, opponent_id, guesses
-            );
-
-            html.push_str(&table_row);
-        }
+        let Some((opponent_id, guesses)) = player.opponent.as_ref() else {
+            return;
+        };
+
+        let (asking_player, guessing_player) = match player.role.as_ref() {
+            Some(Role::AskingPlayer) => (player.id.as_str(), opponent_id.as_str()),
+            Some(Role::GuessingPlayer) if *opponent_id == HOUSE_PLAYER_ID => {
+                ("House", player.id.as_str())
+            }
+            _ => return,
+        };
+
+        let table_row = format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            escape_html(asking_player),
+            escape_html(guessing_player),
+            guesses,
+            escape_html(&player.category)
+        );
+
+        html.push_str(&table_row);
     });
 
     let closing = r##"
@@ -53,11 +161,29 @@ pub fn handle_http_request(
          </html>
     "##;
     html.push_str(closing);
-    let length = html.len();
 
-    let response = format!("{status_line}\r\nContent-Length: {length}\r\n\r\n{html}");
-    stream
-        .write_all(response.as_bytes())
-        .map_err(|_| "error writing http request")?;
-    Ok(())
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn websocket_key_requires_upgrade_header() {
+        let upgrade = "GET /ws HTTP/1.1\r\nHost: localhost\r\nUpgrade: websocket\r\nSec-WebSocket-Key: abc==\r\n\r\n";
+        let request = read_request(&mut Cursor::new(upgrade)).unwrap();
+        assert_eq!(request.path, "/ws");
+        assert_eq!(request.websocket_key.as_deref(), Some("abc=="));
+
+        let plain = "GET /ws HTTP/1.1\r\nSec-WebSocket-Key: abc==\r\n\r\n";
+        let request = read_request(&mut Cursor::new(plain)).unwrap();
+        assert!(request.websocket_key.is_none());
+    }
+
+    #[test]
+    fn invalid_request_line_is_rejected() {
+        assert!(read_request(&mut Cursor::new("\r\n\r\n")).is_err());
+    }
 }
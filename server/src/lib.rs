@@ -0,0 +1,12 @@
+pub mod behaviour;
+pub mod bot;
+pub mod config;
+pub mod http;
+pub mod login_guard;
+mod password;
+pub mod player;
+pub mod recording;
+pub mod riddle_library;
+pub mod tcp;
+pub mod unix_socket;
+pub mod websocket;
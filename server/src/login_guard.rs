@@ -0,0 +1,186 @@
+use guessing_game::logging::log_warn;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Wait after the first failed login of a peer, doubled with every further failure.
+const LOGIN_BACKOFF: Duration = Duration::from_secs(1);
+
+#[derive(Default)]
+struct PeerRecord {
+    connections: usize,
+    failed_logins: u32,
+    retry_at: Option<Instant>,
+}
+
+/// Connection counts and failed logins per peer address.
+///
+/// Failed logins make a peer wait before its next attempt, starting at `LOGIN_BACKOFF` and
+/// doubling up to `lockout`. After `lockout_attempts` failures in a row the peer is locked
+/// out for `lockout`. A successful login resets the count, peers without open connections
+/// are forgotten once they could have retried for `lockout`.
+pub struct LoginGuard {
+    /// Open game connections allowed per peer address, 0 means unlimited.
+    pub max_connections_per_peer: usize,
+    pub lockout_attempts: u32,
+    pub lockout: Duration,
+    peers: Mutex<HashMap<IpAddr, PeerRecord>>,
+}
+
+/// Open connection of a peer, released when dropped.
+pub struct PeerConnection<'a> {
+    guard: &'a LoginGuard,
+    pub peer: IpAddr,
+}
+
+impl Drop for PeerConnection<'_> {
+    fn drop(&mut self) {
+        let mut peers = self.guard.peers.lock().unwrap();
+        if let Some(record) = peers.get_mut(&self.peer) {
+            record.connections = record.connections.saturating_sub(1);
+            if self.guard.is_stale(record, Instant::now()) {
+                peers.remove(&self.peer);
+            }
+        }
+    }
+}
+
+impl LoginGuard {
+    pub fn new(max_connections_per_peer: usize, lockout_attempts: u32, lockout: Duration) -> Self {
+        LoginGuard {
+            max_connections_per_peer,
+            lockout_attempts,
+            lockout,
+            peers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn is_stale(&self, record: &PeerRecord, now: Instant) -> bool {
+        record.connections == 0
+            && record
+                .retry_at
+                .is_none_or(|retry_at| retry_at + self.lockout <= now)
+    }
+
+    /// Counts a new connection of `peer`, `None` when the peer has too many open already.
+    pub fn open_connection(&self, peer: IpAddr) -> Option<PeerConnection<'_>> {
+        let mut peers = self.peers.lock().unwrap();
+        let record = peers.entry(peer).or_default();
+        if self.max_connections_per_peer > 0 && record.connections >= self.max_connections_per_peer
+        {
+            log_warn(format!(
+                "Refusing connection from {}, {} connections are open",
+                peer, record.connections
+            ));
+            return None;
+        }
+
+        record.connections += 1;
+        Some(PeerConnection { guard: self, peer })
+    }
+
+    /// Time `peer` has to wait before it may try to log in again.
+    pub fn login_wait(&self, peer: IpAddr) -> Option<Duration> {
+        self.login_wait_at(peer, Instant::now())
+    }
+
+    fn login_wait_at(&self, peer: IpAddr, now: Instant) -> Option<Duration> {
+        let peers = self.peers.lock().unwrap();
+        let retry_at = peers.get(&peer)?.retry_at?;
+        let wait = retry_at
+            .checked_duration_since(now)
+            .filter(|wait| !wait.is_zero())?;
+        log_warn(format!(
+            "Login attempt from {} while it has to wait {}s",
+            peer,
+            wait.as_secs_f32().ceil()
+        ));
+
+        Some(wait)
+    }
+
+    pub fn login_failed(&self, peer: IpAddr) {
+        self.login_failed_at(peer, Instant::now());
+    }
+
+    fn login_failed_at(&self, peer: IpAddr, now: Instant) {
+        let mut peers = self.peers.lock().unwrap();
+        peers.retain(|_, record| !self.is_stale(record, now));
+        let record = peers.entry(peer).or_default();
+        record.failed_logins += 1;
+
+        let wait = if record.failed_logins >= self.lockout_attempts {
+            log_warn(format!(
+                "Locking out {} for {}s after {} failed logins",
+                peer,
+                self.lockout.as_secs(),
+                record.failed_logins
+            ));
+            self.lockout
+        } else {
+            log_warn(format!(
+                "Failed login from {}, attempt {}",
+                peer, record.failed_logins
+            ));
+            LOGIN_BACKOFF
+                .saturating_mul(1 << (record.failed_logins - 1).min(16))
+                .min(self.lockout)
+        };
+        record.retry_at = Some(now + wait);
+    }
+
+    pub fn login_succeeded(&self, peer: IpAddr) {
+        if let Some(record) = self.peers.lock().unwrap().get_mut(&peer) {
+            record.failed_logins = 0;
+            record.retry_at = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    const PEER: IpAddr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+
+    #[test]
+    fn failed_logins_back_off_until_lockout() {
+        let guard = LoginGuard::new(0, 3, Duration::from_secs(60));
+        let now = Instant::now();
+
+        assert_eq!(guard.login_wait_at(PEER, now), None);
+        guard.login_failed_at(PEER, now);
+        assert_eq!(guard.login_wait_at(PEER, now), Some(LOGIN_BACKOFF));
+        guard.login_failed_at(PEER, now);
+        assert_eq!(guard.login_wait_at(PEER, now), Some(LOGIN_BACKOFF * 2));
+        guard.login_failed_at(PEER, now);
+        assert_eq!(
+            guard.login_wait_at(PEER, now),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(
+            guard.login_wait_at(PEER, now + Duration::from_secs(60)),
+            None
+        );
+
+        guard.login_succeeded(PEER);
+        assert_eq!(guard.login_wait_at(PEER, now), None);
+    }
+
+    #[test]
+    fn connections_are_capped_per_peer() {
+        let guard = LoginGuard::new(1, 3, Duration::from_secs(60));
+        let other_peer = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+        let connection = guard.open_connection(PEER);
+        assert!(connection.is_some());
+        assert!(guard.open_connection(PEER).is_none());
+        assert!(guard.open_connection(other_peer).is_some());
+
+        drop(connection);
+        assert!(guard.open_connection(PEER).is_some());
+        assert!(guard.peers.lock().unwrap().is_empty());
+    }
+}
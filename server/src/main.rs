@@ -1,27 +1,22 @@
 use guessing_game::logging::{self, log_error};
-use http::handle_http_request;
-use password::get_password;
-use player::Player;
+use guessing_game::messages::PlayerId;
+use server::bot::spawn_bots;
+use server::config::get_config;
+use server::http::handle_http_request;
+use server::player::Player;
+use server::tcp::{handle_tcp_client, WireFormat};
+use server::unix_socket::handle_unix_socket_client;
 use std::os::unix::net::UnixListener;
 use std::sync::{Arc, Mutex};
 use std::{collections::HashMap, net::TcpListener, thread};
-use tcp::handle_tcp_client;
-use unix_socket::handle_unix_socket_client;
-
-mod behaviour;
-mod http;
-mod password;
-mod player;
-mod tcp;
-mod unix_socket;
 
 fn main() {
-    let password = get_password();
-    let players: HashMap<String, Player> = HashMap::new();
+    let config = get_config();
+    let players: HashMap<PlayerId, Player> = HashMap::new();
     let player = Arc::new(Mutex::new(players));
     let player_clone = Arc::clone(&player);
-    let password = Arc::new(password);
-    let password_clone = password.clone();
+    let config = Arc::new(config);
+    let config_clone = config.clone();
 
     let tcp_handle = thread::spawn(move || {
         let listener = TcpListener::bind("0.0.0.0:9000").unwrap();
@@ -30,9 +25,14 @@ fn main() {
                 Ok(stream) => {
                     logging::log_info(format!("New connection: {}", stream.peer_addr().unwrap()));
                     let game_status_clone_inner = player_clone.clone();
-                    let password_clone_inner = password_clone.clone();
+                    let config_clone_inner = config_clone.clone();
                     thread::spawn(move || {
-                        handle_tcp_client(stream, game_status_clone_inner, password_clone_inner)
+                        handle_tcp_client(
+                            stream,
+                            game_status_clone_inner,
+                            config_clone_inner,
+                            WireFormat::Binary,
+                        )
                     });
                 }
                 Err(e) => {
@@ -44,6 +44,37 @@ fn main() {
     });
 
     let player_clone = Arc::clone(&player);
+    let config_clone = config.clone();
+    let json_handle = thread::spawn(move || {
+        let listener = TcpListener::bind("0.0.0.0:9001").unwrap();
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    logging::log_info(format!(
+                        "New JSON connection: {}",
+                        stream.peer_addr().unwrap()
+                    ));
+                    let game_status_clone_inner = player_clone.clone();
+                    let config_clone_inner = config_clone.clone();
+                    thread::spawn(move || {
+                        handle_tcp_client(
+                            stream,
+                            game_status_clone_inner,
+                            config_clone_inner,
+                            WireFormat::Json,
+                        )
+                    });
+                }
+                Err(e) => {
+                    log_error(e);
+                }
+            }
+        }
+        drop(listener);
+    });
+
+    let player_clone = Arc::clone(&player);
+    let config_clone = config.clone();
     let http_handle = thread::spawn(move || {
         let listener = TcpListener::bind("0.0.0.0:3000").unwrap();
         for stream in listener.incoming() {
@@ -51,7 +82,10 @@ fn main() {
                 Ok(stream) => {
                     logging::log_info(format!("New connection: {}", stream.peer_addr().unwrap()));
                     let game_status_clone_inner = player_clone.clone();
-                    thread::spawn(move || handle_http_request(stream, game_status_clone_inner));
+                    let config_clone_inner = config_clone.clone();
+                    thread::spawn(move || {
+                        handle_http_request(stream, game_status_clone_inner, config_clone_inner)
+                    });
                 }
                 Err(e) => {
                     log_error(e);
@@ -62,7 +96,7 @@ fn main() {
     });
 
     let player_clone = Arc::clone(&player);
-    let password_clone = password.clone();
+    let config_clone = config.clone();
     let socket_path = "/tmp/guessing_game";
     let _ = std::fs::remove_file(socket_path);
 
@@ -77,12 +111,12 @@ fn main() {
                         stream.peer_addr().unwrap()
                     ));
                     let game_status_clone_inner = player_clone.clone();
-                    let password_clone_inner = password_clone.clone();
+                    let config_clone_inner = config_clone.clone();
                     thread::spawn(move || {
                         handle_unix_socket_client(
                             stream,
                             game_status_clone_inner,
-                            password_clone_inner,
+                            config_clone_inner,
                         )
                     });
                 }
@@ -94,7 +128,10 @@ fn main() {
         drop(listener);
     });
 
+    spawn_bots(player.clone(), config.clone());
+
     let _ = tcp_handle.join();
+    let _ = json_handle.join();
     let _ = unix_socket_handle.join();
     let _ = http_handle.join();
 }
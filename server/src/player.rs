@@ -1,11 +1,47 @@
-use guessing_game::messages::Role;
+use crate::riddle_library::LibraryRiddle;
 use guessing_game::messages::Streamable;
+use guessing_game::messages::{PlayerId, Role};
+use std::collections::VecDeque;
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
 pub struct Player {
-    pub id: String,
+    pub id: PlayerId,
     pub stream: Arc<Mutex<dyn Streamable>>,
-    pub opponent: Option<(String, usize)>,
+    pub opponent: Option<(PlayerId, usize)>,
     pub question: Option<String>,
     pub role: Option<Role>,
+    pub preferred_categories: Vec<String>,
+    pub category: String,
+    pub hints_used: u8,
+    pub max_guesses: u8,
+    pub house_riddle: Option<LibraryRiddle>,
+    pub chat_timestamps: VecDeque<Instant>,
+}
+
+impl Player {
+    pub fn new(id: PlayerId, stream: Arc<Mutex<dyn Streamable>>) -> Self {
+        Player {
+            id,
+            stream,
+            opponent: None,
+            question: None,
+            role: None,
+            preferred_categories: Vec::new(),
+            category: String::new(),
+            hints_used: 0,
+            max_guesses: 0,
+            house_riddle: None,
+            chat_timestamps: VecDeque::new(),
+        }
+    }
+
+    pub fn leave_game(&mut self) {
+        self.opponent = None;
+        self.question = None;
+        self.category.clear();
+        self.hints_used = 0;
+        self.max_guesses = 0;
+        self.house_riddle = None;
+    }
 }
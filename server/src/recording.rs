@@ -0,0 +1,27 @@
+use crate::config::Config;
+use guessing_game::logging::log_warn;
+use guessing_game::messages::get_random_id;
+use std::fs::File;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Creates a recording file for a new connection when `--record-dir` is set.
+pub fn open_recording(config: &Config) -> Option<File> {
+    let record_dir = config.record_dir.as_ref()?;
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    let path = record_dir.join(format!("{}-{}.rec", timestamp, get_random_id()));
+
+    match File::create(&path) {
+        Ok(file) => Some(file),
+        Err(err) => {
+            log_warn(format!(
+                "Can not create recording {}: {}",
+                path.display(),
+                err
+            ));
+            None
+        }
+    }
+}
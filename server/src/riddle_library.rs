@@ -0,0 +1,205 @@
+use guessing_game::messages::Difficulty;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Deserializer};
+use std::fs;
+use std::path::Path;
+
+fn deserialize_difficulty<'de, D>(deserializer: D) -> Result<Difficulty, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let difficulty = String::deserialize(deserializer)?;
+    difficulty.parse().map_err(serde::de::Error::custom)
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct LibraryRiddle {
+    pub question: String,
+    pub answers: Vec<String>,
+    #[serde(default)]
+    pub hints: Vec<String>,
+    #[serde(default)]
+    pub category: String,
+    #[serde(default, deserialize_with = "deserialize_difficulty")]
+    pub difficulty: Difficulty,
+}
+
+impl LibraryRiddle {
+    pub fn is_answer(&self, guess: &str) -> bool {
+        let guess = guess.trim();
+        self.answers
+            .iter()
+            .any(|answer| answer.trim().eq_ignore_ascii_case(guess))
+    }
+
+    pub fn is_in_categories(&self, categories: &[String]) -> bool {
+        categories.is_empty()
+            || categories
+                .iter()
+                .any(|category| category.eq_ignore_ascii_case(&self.category))
+    }
+}
+
+#[derive(Deserialize)]
+struct RiddlePack {
+    riddles: Vec<LibraryRiddle>,
+}
+
+impl RiddlePack {
+    /// Rejects texts that do not fit the one byte length prefix of the binary format.
+    fn check_lengths(self) -> Result<Self, String> {
+        for riddle in &self.riddles {
+            let fields = [
+                ("question", &riddle.question),
+                ("category", &riddle.category),
+            ]
+            .into_iter()
+            .chain(riddle.answers.iter().map(|answer| ("answer", answer)))
+            .chain(riddle.hints.iter().map(|hint| ("hint", hint)));
+            for (field, text) in fields {
+                if text.len() > u8::MAX as usize {
+                    return Err(format!(
+                        "{} of riddle \"{}\" is longer than {} bytes",
+                        field,
+                        riddle.question.chars().take(40).collect::<String>(),
+                        u8::MAX
+                    ));
+                }
+            }
+        }
+
+        Ok(self)
+    }
+}
+
+#[derive(Default)]
+pub struct RiddleLibrary {
+    riddles: Vec<LibraryRiddle>,
+}
+
+impl RiddleLibrary {
+    pub fn load_dir(path: &Path) -> Result<Self, String> {
+        let mut library = RiddleLibrary::default();
+        let mut entries = fs::read_dir(path)
+            .map_err(|err| format!("Can not read riddle directory {}: {}", path.display(), err))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .collect::<Vec<_>>();
+        entries.sort();
+
+        for entry in entries {
+            match entry.extension().and_then(|extension| extension.to_str()) {
+                Some("toml") | Some("json") => library.load_file(&entry)?,
+                _ => continue,
+            }
+        }
+
+        Ok(library)
+    }
+
+    pub fn load_file(&mut self, path: &Path) -> Result<(), String> {
+        let content = fs::read_to_string(path)
+            .map_err(|err| format!("Can not read riddle pack {}: {}", path.display(), err))?;
+        let pack = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => parse_json_pack(&content),
+            _ => parse_toml_pack(&content),
+        }
+        .map_err(|err| format!("Invalid riddle pack {}: {}", path.display(), err))?;
+
+        self.riddles.extend(pack.riddles);
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.riddles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.riddles.is_empty()
+    }
+
+    pub fn find_by_question(&self, question: &str) -> Option<&LibraryRiddle> {
+        self.riddles
+            .iter()
+            .find(|riddle| riddle.question.eq_ignore_ascii_case(question.trim()))
+    }
+
+    pub fn answers(&self) -> impl Iterator<Item = &String> {
+        self.riddles.iter().flat_map(|riddle| riddle.answers.iter())
+    }
+
+    pub fn random(&self, categories: &[String]) -> Option<&LibraryRiddle> {
+        let riddles = self
+            .riddles
+            .iter()
+            .filter(|riddle| riddle.is_in_categories(categories))
+            .collect::<Vec<_>>();
+
+        riddles.choose(&mut rand::thread_rng()).copied()
+    }
+}
+
+fn parse_toml_pack(content: &str) -> Result<RiddlePack, String> {
+    toml::from_str::<RiddlePack>(content)
+        .map_err(|err| err.to_string())?
+        .check_lengths()
+}
+
+fn parse_json_pack(content: &str) -> Result<RiddlePack, String> {
+    serde_json::from_str::<RiddlePack>(content)
+        .map_err(|err| err.to_string())?
+        .check_lengths()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_toml_riddle_pack() {
+        let pack = parse_toml_pack(
+            r#"
+            [[riddles]]
+            question = "What has keys but can't open locks?"
+            answers = ["piano", "a piano"]
+            hints = ["It makes music"]
+            category = "objects"
+            difficulty = "easy"
+            "#,
+        )
+        .expect("Pack should parse");
+
+        let riddle = &pack.riddles[0];
+        assert_eq!(riddle.category, "objects");
+        assert_eq!(riddle.difficulty, Difficulty::Easy);
+        assert_eq!(riddle.hints.len(), 1);
+        assert!(riddle.is_answer(" A Piano "));
+        assert!(!riddle.is_answer("guitar"));
+        assert!(riddle.is_in_categories(&[String::from("Objects")]));
+        assert!(!riddle.is_in_categories(&[String::from("nature")]));
+    }
+
+    #[test]
+    fn parse_json_riddle_pack_with_defaults() {
+        let pack = parse_json_pack(
+            r#"{"riddles": [{"question": "What gets wetter as it dries?", "answers": ["towel"]}]}"#,
+        )
+        .expect("Pack should parse");
+
+        let riddle = &pack.riddles[0];
+        assert!(riddle.hints.is_empty());
+        assert_eq!(riddle.difficulty, Difficulty::Medium);
+    }
+
+    #[test]
+    fn over_long_question_is_rejected() {
+        let content = format!(
+            r#"{{"riddles": [{{"question": "{}", "answers": ["towel"]}}]}}"#,
+            "a".repeat(256)
+        );
+
+        let err = parse_json_pack(&content)
+            .err()
+            .expect("Pack should be rejected");
+        assert!(err.starts_with("question of riddle"));
+    }
+}
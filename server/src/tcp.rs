@@ -1,27 +1,106 @@
 use crate::behaviour::handle_game_client;
+use crate::config::Config;
 use crate::player::Player;
+use crate::recording::open_recording;
 use guessing_game::logging::log_error;
-use guessing_game::messages::Streamable;
-use guessing_game::messages::TcpMessageStream;
+use guessing_game::messages::{PlayerId, RecordingStream, Streamable};
+use guessing_game::messages::{TcpJsonStream, TcpMessageStream};
+use std::net::IpAddr;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use std::{collections::HashMap, net::TcpStream};
 
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WireFormat {
+    /// `pack`ed frames, used by the native clients.
+    Binary,
+    /// One JSON message per line.
+    Json,
+}
+
 pub fn handle_tcp_client(
     stream: TcpStream,
-    players: Arc<Mutex<HashMap<String, Player>>>,
-    password_secret: Arc<String>,
+    players: Arc<Mutex<HashMap<PlayerId, Player>>>,
+    config: Arc<Config>,
+    wire_format: WireFormat,
 ) {
-    stream
-        .set_nonblocking(true)
-        .expect("Set non blocking for tcp stream failed");
+    let peer = match stream.peer_addr() {
+        Ok(address) => address.ip(),
+        Err(err) => {
+            log_error(err);
+            return;
+        }
+    };
+    // refused connections are closed by dropping the stream
+    let Some(_connection) = config.login_guard.open_connection(peer) else {
+        return;
+    };
 
-    let mut tcp_stream = TcpMessageStream { stream };
+    match open_tcp_stream(stream, &config, wire_format) {
+        Ok(tcp_stream) => record_tcp_client(tcp_stream, players, &config, peer),
+        Err(err) => log_error(err),
+    }
+}
+
+fn open_tcp_stream(
+    stream: TcpStream,
+    config: &Config,
+    wire_format: WireFormat,
+) -> Result<Box<dyn Streamable>, String> {
+    let Some(tls) = config.tls.as_ref() else {
+        stream.set_nonblocking(true).map_err(|e| e.to_string())?;
+        return Ok(match wire_format {
+            WireFormat::Binary => Box::new(TcpMessageStream::from(stream)),
+            WireFormat::Json => Box::new(TcpJsonStream::from(stream)),
+        });
+    };
+
+    // the clone shares the socket, so its options can be changed after the handshake
+    let socket = stream.try_clone().map_err(|e| e.to_string())?;
+    socket
+        .set_read_timeout(Some(TLS_HANDSHAKE_TIMEOUT))
+        .map_err(|e| e.to_string())?;
+    let tls_stream: Box<dyn Streamable> = match wire_format {
+        WireFormat::Binary => Box::new(tls.accept(stream)?),
+        WireFormat::Json => Box::new(tls.accept_json(stream)?),
+    };
+    socket.set_read_timeout(None).map_err(|e| e.to_string())?;
+    socket.set_nonblocking(true).map_err(|e| e.to_string())?;
+
+    Ok(tls_stream)
+}
+
+pub(crate) fn record_tcp_client(
+    tcp_stream: impl Streamable + 'static,
+    players: Arc<Mutex<HashMap<PlayerId, Player>>>,
+    config: &Config,
+    peer: IpAddr,
+) {
+    match open_recording(config) {
+        Some(recording) => serve_tcp_client(
+            RecordingStream::new(tcp_stream, recording),
+            players,
+            config,
+            peer,
+        ),
+        None => serve_tcp_client(tcp_stream, players, config, peer),
+    }
+}
+
+fn serve_tcp_client(
+    mut tcp_stream: impl Streamable + 'static,
+    players: Arc<Mutex<HashMap<PlayerId, Player>>>,
+    config: &Config,
+    peer: IpAddr,
+) {
     if let Err(err) = tcp_stream.write(&guessing_game::messages::Message::GameStart) {
         log_error(err);
         return;
     }
 
-    if let Err(err) = handle_game_client(tcp_stream, players.clone(), &password_secret) {
+    if let Err(err) = handle_game_client(tcp_stream, players.clone(), config, Some(peer)) {
         log_error(err.err);
     };
 }
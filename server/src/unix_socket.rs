@@ -1,23 +1,40 @@
 use crate::behaviour::handle_game_client;
 use crate::behaviour::remove_player;
+use crate::config::Config;
 use crate::player::Player;
+use crate::recording::open_recording;
 use guessing_game::logging::log_error;
-use guessing_game::messages::Streamable;
 use guessing_game::messages::UnixMessageStream;
+use guessing_game::messages::{PlayerId, RecordingStream, Streamable};
 use std::collections::HashMap;
 use std::os::unix::net::UnixStream;
 use std::sync::{Arc, Mutex};
 
 pub fn handle_unix_socket_client(
     stream: UnixStream,
-    players: Arc<Mutex<HashMap<String, Player>>>,
-    password_secret: Arc<String>,
+    players: Arc<Mutex<HashMap<PlayerId, Player>>>,
+    config: Arc<Config>,
 ) {
     stream
         .set_nonblocking(true)
         .expect("Set non blocking failed");
 
-    let mut unix_stream = UnixMessageStream { stream };
+    let unix_stream = UnixMessageStream::from(stream);
+    match open_recording(&config) {
+        Some(recording) => serve_unix_socket_client(
+            RecordingStream::new(unix_stream, recording),
+            players,
+            &config,
+        ),
+        None => serve_unix_socket_client(unix_stream, players, &config),
+    }
+}
+
+fn serve_unix_socket_client(
+    mut unix_stream: impl Streamable + 'static,
+    players: Arc<Mutex<HashMap<PlayerId, Player>>>,
+    config: &Config,
+) {
     if let Err(err) = unix_stream.write(&guessing_game::messages::Message::GameStart) {
         log_error(err);
         if let Err(e) = unix_stream.shutdown() {
@@ -26,9 +43,9 @@ pub fn handle_unix_socket_client(
         return;
     }
 
-    if let Err(err) = handle_game_client(unix_stream, players.clone(), &password_secret) {
-        if err.player_id.is_some() {
-            remove_player(players, &err.player_id.unwrap())
+    if let Err(err) = handle_game_client(unix_stream, players.clone(), config, None) {
+        if let Some(player_id) = err.player_id {
+            remove_player(players, &player_id)
         }
         log_error(err.err);
     };
@@ -0,0 +1,44 @@
+use crate::config::Config;
+use crate::player::Player;
+use crate::tcp::record_tcp_client;
+use guessing_game::logging::log_error;
+use guessing_game::messages::{websocket_accept_key, PlayerId, WebSocketMessageStream};
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::{collections::HashMap, net::TcpStream};
+
+/// Answers the upgrade request of a browser client and serves it like a TCP client.
+pub fn handle_websocket_client(
+    mut stream: TcpStream,
+    websocket_key: &str,
+    read_ahead: Vec<u8>,
+    players: Arc<Mutex<HashMap<PlayerId, Player>>>,
+    config: Arc<Config>,
+) {
+    let peer = match stream.peer_addr() {
+        Ok(address) => address.ip(),
+        Err(err) => {
+            log_error(err);
+            return;
+        }
+    };
+    let Some(_connection) = config.login_guard.open_connection(peer) else {
+        return;
+    };
+
+    let response = format!(
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        websocket_accept_key(websocket_key)
+    );
+    let upgraded = stream
+        .write_all(response.as_bytes())
+        .and_then(|_| stream.set_read_timeout(None))
+        .and_then(|_| stream.set_nonblocking(true));
+    if let Err(err) = upgraded {
+        log_error(err);
+        return;
+    }
+
+    let websocket_stream = WebSocketMessageStream::upgraded(stream, read_ahead);
+    record_tcp_client(websocket_stream, players, &config, peer);
+}
@@ -0,0 +1,522 @@
+use guessing_game::client_core::{ClientError, GameClient, GameEvent};
+use guessing_game::messages::{
+    memory_stream_pair, ChatScope, ErrorCode, Frame, MemoryMessageStream, Message, MessageError,
+    Password, PlayerId, PlayerRole, Riddle, Role, Streamable, HOUSE_PLAYER_ID,
+};
+use server::behaviour::handle_game_client;
+use server::config::Config;
+use server::login_guard::LoginGuard;
+use server::player::Player;
+use server::riddle_library::RiddleLibrary;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+const PASSWORD: &str = "secret";
+
+type Players = Arc<Mutex<HashMap<PlayerId, Player>>>;
+
+fn test_config() -> Config {
+    Config {
+        password: PASSWORD.into(),
+        hint_budget: 3,
+        max_guesses: 10,
+        riddle_library: RiddleLibrary::default(),
+        asking_bots: 0,
+        guessing_bots: 0,
+        record_dir: None,
+        tls: None,
+        login_guard: LoginGuard::new(0, 5, Duration::from_secs(300)),
+    }
+}
+
+struct TestServer {
+    players: Players,
+    config: Arc<Config>,
+}
+
+impl TestServer {
+    fn new(config: Config) -> Self {
+        TestServer {
+            players: Arc::new(Mutex::new(HashMap::new())),
+            config: Arc::new(config),
+        }
+    }
+
+    fn connect(&self) -> GameClient<MemoryMessageStream> {
+        self.connect_from(None)
+    }
+
+    fn connect_from(&self, peer: Option<IpAddr>) -> GameClient<MemoryMessageStream> {
+        let (client_stream, server_stream) = memory_stream_pair();
+        let players = self.players.clone();
+        let config = self.config.clone();
+        thread::spawn(move || handle_game_client(server_stream, players, &config, peer));
+
+        GameClient::new(client_stream)
+    }
+
+    fn login(&self) -> GameClient<MemoryMessageStream> {
+        let mut client = self.connect();
+        client.login(PASSWORD).expect("Login should not fail");
+        client
+    }
+
+    fn guessing_player(&self, categories: Vec<String>) -> GameClient<MemoryMessageStream> {
+        let mut client = self.login();
+        client
+            .register_role(PlayerRole {
+                role: Role::GuessingPlayer,
+                categories,
+            })
+            .unwrap();
+        // the server handles messages in order, so the role is stored once this returns
+        client.list_opponents(None).unwrap();
+        client
+    }
+
+    fn asking_player(&self) -> GameClient<MemoryMessageStream> {
+        let mut client = self.login();
+        client.register_role(Role::AskingPlayer.into()).unwrap();
+        client
+    }
+}
+
+fn start_game(
+    asking_player: &mut GameClient<MemoryMessageStream>,
+    guessing_player: &mut GameClient<MemoryMessageStream>,
+    riddle: Riddle,
+) {
+    let guessing_player_id = guessing_player.player_id().unwrap().to_owned();
+    asking_player
+        .send_riddle(Riddle {
+            guessing_player: guessing_player_id,
+            ..riddle
+        })
+        .unwrap();
+
+    match guessing_player.next_event().unwrap() {
+        GameEvent::RiddleReceived(riddle) => {
+            assert_eq!(riddle.asking_player, asking_player.player_id().unwrap())
+        }
+        event => panic!("event should be riddle, got {:?}", event),
+    }
+}
+
+fn piano_riddle() -> Riddle {
+    Riddle {
+        message: "piano".into(),
+        hint: Some("88 keys".into()),
+        category: "music".into(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn login_with_wrong_password_is_rejected() {
+    let server = TestServer::new(test_config());
+    let mut client = server.connect();
+
+    match client.login("wrong") {
+        Err(ClientError::Rejected(error_message)) => {
+            assert_eq!(error_message.code, ErrorCode::InvalidPassword)
+        }
+        result => panic!("login should be rejected, got {:?}", result),
+    }
+    assert!(server.players.lock().unwrap().is_empty());
+}
+
+#[test]
+fn failed_logins_make_the_peer_wait() {
+    let server = TestServer::new(test_config());
+    let peer = "10.0.0.1".parse().ok();
+
+    let rejected_code =
+        |mut client: GameClient<MemoryMessageStream>, password| match client.login(password) {
+            Err(ClientError::Rejected(error_message)) => error_message.code,
+            result => panic!("login should be rejected, got {:?}", result),
+        };
+    assert_eq!(
+        rejected_code(server.connect_from(peer), "wrong"),
+        ErrorCode::InvalidPassword
+    );
+    assert_eq!(
+        rejected_code(server.connect_from(peer), PASSWORD),
+        ErrorCode::RateLimited
+    );
+
+    let mut other_peer_client = server.connect_from("10.0.0.2".parse().ok());
+    assert!(other_peer_client.login(PASSWORD).is_ok());
+}
+
+#[test]
+fn flooding_requests_are_rate_limited() {
+    let server = TestServer::new(test_config());
+    let mut player = server.guessing_player(vec![]);
+
+    let rejection = (0..10).find_map(|_| match player.list_opponents(None) {
+        Err(ClientError::Rejected(error_message)) => Some(error_message.code),
+        _ => None,
+    });
+    assert_eq!(rejection, Some(ErrorCode::RateLimited));
+    assert!(server
+        .players
+        .lock()
+        .unwrap()
+        .contains_key(&player.player_id().unwrap()));
+}
+
+fn read_frame(stream: &mut MemoryMessageStream) -> Frame {
+    loop {
+        match stream.read_frame() {
+            Err(MessageError::EmptyRead) => thread::yield_now(),
+            frame => return frame.unwrap(),
+        }
+    }
+}
+
+#[test]
+fn responses_echo_request_ids() {
+    let server = TestServer::new(test_config());
+    let (mut stream, server_stream) = memory_stream_pair();
+    let players = server.players.clone();
+    let config = server.config.clone();
+    thread::spawn(move || handle_game_client(server_stream, players, &config, None));
+
+    stream
+        .write_frame(&Frame {
+            request_id: Some(41),
+            message: Message::Password(Password {
+                password: PASSWORD.into(),
+            }),
+        })
+        .unwrap();
+    assert!(matches!(
+        read_frame(&mut stream),
+        Frame {
+            request_id: Some(41),
+            message: Message::PlayerId(_),
+        }
+    ));
+
+    stream
+        .write_frame(&Frame {
+            request_id: Some(42),
+            message: Message::RequestGuessingPlayers(None),
+        })
+        .unwrap();
+    assert!(matches!(
+        read_frame(&mut stream),
+        Frame {
+            request_id: Some(42),
+            message: Message::PlayerList(_),
+        }
+    ));
+
+    stream
+        .write(&Message::RequestGuessingPlayers(None))
+        .unwrap();
+    assert!(matches!(
+        read_frame(&mut stream),
+        Frame {
+            request_id: None,
+            message: Message::PlayerList(_),
+        }
+    ));
+}
+
+#[test]
+fn lobby_lists_free_guessing_players_by_category() {
+    let server = TestServer::new(test_config());
+    let music_player = server.guessing_player(vec!["music".into()]);
+    let any_player = server.guessing_player(vec![]);
+    let history_player = server.guessing_player(vec!["history".into()]);
+    let mut asking_player = server.asking_player();
+
+    let mut all_players = asking_player.list_opponents(None).unwrap();
+    all_players.sort();
+    let mut expected = vec![
+        music_player.player_id().unwrap().to_owned(),
+        any_player.player_id().unwrap().to_owned(),
+        history_player.player_id().unwrap().to_owned(),
+    ];
+    expected.sort();
+    assert_eq!(all_players, expected);
+
+    let mut music_players = asking_player.list_opponents(Some("Music".into())).unwrap();
+    music_players.sort();
+    let mut expected = vec![
+        music_player.player_id().unwrap().to_owned(),
+        any_player.player_id().unwrap().to_owned(),
+    ];
+    expected.sort();
+    assert_eq!(music_players, expected);
+}
+
+#[test]
+fn full_game_is_won_by_guessing_player() {
+    let server = TestServer::new(test_config());
+    let mut guessing_player = server.guessing_player(vec![]);
+    let mut asking_player = server.asking_player();
+    start_game(&mut asking_player, &mut guessing_player, piano_riddle());
+
+    guessing_player.guess("drum").unwrap();
+    assert!(matches!(
+        asking_player.next_event().unwrap(),
+        GameEvent::GuessEvaluated { guessed: false }
+    ));
+    asking_player
+        .evaluate(false, Some("black and white".into()))
+        .unwrap();
+    match guessing_player.next_event().unwrap() {
+        GameEvent::Evaluated(evaluation) => {
+            assert!(!evaluation.guessed);
+            assert_eq!(evaluation.hint.as_deref(), Some("black and white"));
+            assert_eq!(evaluation.hints_remaining, 1);
+        }
+        event => panic!("event should be evaluation, got {:?}", event),
+    }
+
+    guessing_player.guess("piano").unwrap();
+    assert!(matches!(
+        asking_player.next_event().unwrap(),
+        GameEvent::GuessEvaluated { guessed: true }
+    ));
+    asking_player.evaluate(true, None).unwrap();
+    match guessing_player.next_event().unwrap() {
+        GameEvent::Evaluated(evaluation) => {
+            assert!(evaluation.guessed);
+            assert_eq!(evaluation.score, 65);
+        }
+        event => panic!("event should be evaluation, got {:?}", event),
+    }
+}
+
+#[test]
+fn game_ends_when_guess_limit_is_reached() {
+    let server = TestServer::new(test_config());
+    let mut guessing_player = server.guessing_player(vec![]);
+    let mut asking_player = server.asking_player();
+    start_game(
+        &mut asking_player,
+        &mut guessing_player,
+        Riddle {
+            max_guesses: 1,
+            ..piano_riddle()
+        },
+    );
+
+    guessing_player.guess("drum").unwrap();
+
+    for client in [&mut guessing_player, &mut asking_player] {
+        match client.next_event().unwrap() {
+            GameEvent::GameOver(game_over) => assert_eq!(game_over.answer, "piano"),
+            event => panic!("event should be game over, got {:?}", event),
+        }
+    }
+    assert!(server
+        .players
+        .lock()
+        .unwrap()
+        .values()
+        .all(|player| player.opponent.is_none()));
+}
+
+#[test]
+fn hint_request_is_refused_when_budget_is_spent() {
+    let server = TestServer::new(Config {
+        hint_budget: 1,
+        ..test_config()
+    });
+    let mut guessing_player = server.guessing_player(vec![]);
+    let mut asking_player = server.asking_player();
+    start_game(&mut asking_player, &mut guessing_player, piano_riddle());
+
+    guessing_player.request_hint().unwrap();
+    match guessing_player.next_event().unwrap() {
+        GameEvent::Error(error_message) => assert_eq!(error_message.code, ErrorCode::NoHintsLeft),
+        event => panic!("event should be error, got {:?}", event),
+    }
+    assert!(asking_player.poll_event().unwrap().is_none());
+}
+
+#[test]
+fn hint_request_without_game_is_rejected_without_disconnecting() {
+    let server = TestServer::new(test_config());
+    let mut guessing_player = server.guessing_player(vec![]);
+
+    guessing_player.request_hint().unwrap();
+    match guessing_player.next_event().unwrap() {
+        GameEvent::Error(error_message) => assert_eq!(error_message.code, ErrorCode::NoOpponent),
+        event => panic!("event should be error, got {:?}", event),
+    }
+    assert!(guessing_player.list_opponents(None).is_ok());
+}
+
+#[test]
+fn wrong_role_closes_connection_with_reason() {
+    let server = TestServer::new(test_config());
+    let mut guessing_player = server.guessing_player(vec![]);
+
+    guessing_player.evaluate(true, None).unwrap();
+    match guessing_player.next_event().unwrap() {
+        GameEvent::Error(error_message) => assert_eq!(error_message.code, ErrorCode::WrongRole),
+        event => panic!("event should be error, got {:?}", event),
+    }
+    assert!(guessing_player.next_event().is_err());
+    assert!(server.players.lock().unwrap().is_empty());
+}
+
+#[test]
+fn opponent_is_notified_when_player_disconnects() {
+    let server = TestServer::new(test_config());
+    let mut guessing_player = server.guessing_player(vec![]);
+    let mut asking_player = server.asking_player();
+    start_game(&mut asking_player, &mut guessing_player, piano_riddle());
+    let asking_player_id = asking_player.player_id().unwrap().to_owned();
+
+    asking_player.shutdown().unwrap();
+
+    match guessing_player.next_event().unwrap() {
+        GameEvent::PlayerNotAvailable(player_id) => assert_eq!(player_id, asking_player_id),
+        event => panic!("event should be player not available, got {:?}", event),
+    }
+    assert!(!server
+        .players
+        .lock()
+        .unwrap()
+        .contains_key(&asking_player_id));
+}
+
+#[test]
+fn guess_for_another_game_is_forbidden() {
+    let server = TestServer::new(test_config());
+    let mut guessing_player = server.guessing_player(vec![]);
+    let mut asking_player = server.asking_player();
+    let other_asking_player = server.asking_player();
+    start_game(&mut asking_player, &mut guessing_player, piano_riddle());
+    let guessing_player_id = guessing_player.player_id().unwrap();
+    let other_asking_player_id = other_asking_player.player_id().unwrap();
+
+    let spoofed_guess = Message::Riddle(Riddle {
+        sender: guessing_player_id,
+        asking_player: other_asking_player_id,
+        guessing_player: guessing_player_id,
+        message: "piano".into(),
+        ..Default::default()
+    });
+    match guessing_player.request(spoofed_guess, |_| false, "error") {
+        Err(ClientError::Rejected(error_message)) => {
+            assert_eq!(error_message.code, ErrorCode::Forbidden)
+        }
+        result => panic!("guess should be rejected, got {:?}", result),
+    }
+    assert!(server.players.lock().unwrap()[&other_asking_player_id]
+        .opponent
+        .is_none());
+}
+
+#[test]
+fn riddle_for_player_in_game_is_refused() {
+    let server = TestServer::new(test_config());
+    let mut guessing_player = server.guessing_player(vec![]);
+    let mut asking_player = server.asking_player();
+    let mut other_asking_player = server.asking_player();
+    start_game(&mut asking_player, &mut guessing_player, piano_riddle());
+    let guessing_player_id = guessing_player.player_id().unwrap();
+
+    other_asking_player
+        .send_riddle(Riddle {
+            guessing_player: guessing_player_id,
+            ..piano_riddle()
+        })
+        .unwrap();
+
+    match other_asking_player.next_event().unwrap() {
+        GameEvent::PlayerNotAvailable(player_id) => assert_eq!(player_id, guessing_player_id),
+        event => panic!("event should be player not available, got {:?}", event),
+    }
+    let players = server.players.lock().unwrap();
+    assert_eq!(
+        players[&guessing_player_id]
+            .opponent
+            .map(|(opponent, _)| opponent),
+        asking_player.player_id()
+    );
+}
+
+#[test]
+fn server_only_messages_are_forbidden() {
+    let server = TestServer::new(test_config());
+    let mut player = server.guessing_player(vec![]);
+    let other_player = server.guessing_player(vec![]);
+    let other_player_id = other_player.player_id().unwrap();
+
+    match player.request(
+        Message::PlayerNotAvailable(other_player_id),
+        |_| false,
+        "error",
+    ) {
+        Err(ClientError::Rejected(error_message)) => {
+            assert_eq!(error_message.code, ErrorCode::Forbidden)
+        }
+        result => panic!("message should be rejected, got {:?}", result),
+    }
+    assert!(server
+        .players
+        .lock()
+        .unwrap()
+        .contains_key(&other_player_id));
+}
+
+#[test]
+fn lobby_chat_reaches_players_outside_games() {
+    let server = TestServer::new(test_config());
+    let mut sender = server.guessing_player(vec![]);
+    let mut receiver = server.guessing_player(vec![]);
+
+    sender.chat(ChatScope::Lobby, "hello").unwrap();
+
+    match receiver.next_event().unwrap() {
+        GameEvent::Chat(chat_message) => {
+            assert_eq!(chat_message.sender, sender.player_id().unwrap());
+            assert_eq!(chat_message.text, "hello");
+        }
+        event => panic!("event should be chat, got {:?}", event),
+    }
+}
+
+#[test]
+fn house_evaluates_guesses_from_riddle_library() {
+    let server = TestServer::new(Config {
+        riddle_library: RiddleLibrary::load_dir(Path::new("riddles")).unwrap(),
+        ..test_config()
+    });
+    let mut guessing_player = server.guessing_player(vec![]);
+
+    guessing_player.request_house_riddle().unwrap();
+    let riddle = match guessing_player.next_event().unwrap() {
+        GameEvent::RiddleReceived(riddle) => riddle,
+        event => panic!("event should be riddle, got {:?}", event),
+    };
+    assert_eq!(riddle.asking_player, HOUSE_PLAYER_ID);
+
+    let answer = server
+        .config
+        .riddle_library
+        .find_by_question(&riddle.message)
+        .map(|library_riddle| library_riddle.answers[0].clone())
+        .unwrap();
+    guessing_player.guess(&answer).unwrap();
+
+    match guessing_player.next_event().unwrap() {
+        GameEvent::Evaluated(evaluation) => {
+            assert!(evaluation.guessed);
+            assert_eq!(evaluation.score, 100);
+        }
+        event => panic!("event should be evaluation, got {:?}", event),
+    }
+}
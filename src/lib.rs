@@ -1,2 +1,3 @@
 pub use messages;
 pub use logging;
+pub use client_core;